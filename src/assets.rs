@@ -0,0 +1,328 @@
+use std::{
+    cell::Cell,
+    collections::HashMap,
+    fmt,
+    future::Future,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    pin::Pin,
+    rc::Rc,
+};
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use serde::Deserialize;
+use web_sys::HtmlImageElement;
+
+use crate::{
+    browser,
+    engine::{self, Audio, Sound, SpriteSheet},
+    sound::SynthParams,
+};
+
+#[derive(Deserialize)]
+pub struct Manifest {
+    #[serde(default)]
+    pub images: Vec<String>,
+    #[serde(default)]
+    pub sheets: Vec<String>,
+    #[serde(default)]
+    pub sounds: Vec<String>,
+    #[serde(default)]
+    pub synths: HashMap<String, SynthParams>,
+}
+
+#[derive(Clone, Default)]
+pub struct Progress {
+    loaded: Rc<Cell<usize>>,
+    total: Rc<Cell<usize>>,
+}
+
+pub struct Handle<T> {
+    id: usize,
+    asset: PhantomData<fn() -> T>,
+}
+
+pub struct Storage<T> {
+    ids: HashMap<String, usize>,
+    paths: Vec<String>,
+    loaded: Vec<Option<Rc<T>>>,
+}
+
+pub trait Asset: Sized {
+    fn storage(assets: &Assets) -> &Storage<Self>;
+    fn storage_mut(assets: &mut Assets) -> &mut Storage<Self>;
+}
+
+pub struct Assets {
+    images: Storage<HtmlImageElement>,
+    sheets: Storage<SpriteSheet>,
+    sounds: Storage<Sound>,
+    synths: HashMap<String, SynthParams>,
+}
+
+enum Loaded {
+    Image(Handle<HtmlImageElement>, HtmlImageElement),
+    Sheet(Handle<SpriteSheet>, SpriteSheet),
+    Sound(Handle<Sound>, Sound),
+}
+
+type LoadFuture<'a> = Pin<Box<dyn Future<Output = Result<Loaded>> + 'a>>;
+
+impl Progress {
+    pub fn fraction(&self) -> f32 {
+        if self.total.get() == 0 {
+            0.0
+        } else {
+            self.loaded.get() as f32 / self.total.get() as f32
+        }
+    }
+
+    fn start(&self, total: usize) {
+        self.loaded.set(0);
+        self.total.set(total);
+    }
+
+    fn advance(&self) {
+        self.loaded.set(self.loaded.get() + 1);
+    }
+}
+
+impl<T> Handle<T> {
+    fn new(id: usize) -> Self {
+        Handle {
+            id,
+            asset: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Handle({})", self.id)
+    }
+}
+
+impl<T> Default for Storage<T> {
+    fn default() -> Self {
+        Storage {
+            ids: HashMap::new(),
+            paths: vec![],
+            loaded: vec![],
+        }
+    }
+}
+
+impl<T> Storage<T> {
+    fn register(&mut self, path: &str) -> Handle<T> {
+        if let Some(handle) = self.find(path) {
+            return handle;
+        }
+
+        let id = self.paths.len();
+        self.ids.insert(path.to_string(), id);
+        self.paths.push(path.to_string());
+        self.loaded.push(None);
+        Handle::new(id)
+    }
+
+    fn find(&self, path: &str) -> Option<Handle<T>> {
+        self.ids.get(path).map(|id| Handle::new(*id))
+    }
+
+    fn get(&self, handle: Handle<T>) -> Option<Rc<T>> {
+        self.loaded.get(handle.id).cloned().flatten()
+    }
+
+    fn pending(&self) -> Vec<(Handle<T>, String)> {
+        self.loaded
+            .iter()
+            .enumerate()
+            .filter(|(_, asset)| asset.is_none())
+            .map(|(id, _)| (Handle::new(id), self.paths[id].clone()))
+            .collect()
+    }
+
+    fn insert(&mut self, handle: Handle<T>, asset: T) {
+        self.loaded[handle.id] = Some(Rc::new(asset));
+    }
+}
+
+impl Asset for HtmlImageElement {
+    fn storage(assets: &Assets) -> &Storage<Self> {
+        &assets.images
+    }
+
+    fn storage_mut(assets: &mut Assets) -> &mut Storage<Self> {
+        &mut assets.images
+    }
+}
+
+impl Asset for SpriteSheet {
+    fn storage(assets: &Assets) -> &Storage<Self> {
+        &assets.sheets
+    }
+
+    fn storage_mut(assets: &mut Assets) -> &mut Storage<Self> {
+        &mut assets.sheets
+    }
+}
+
+impl Asset for Sound {
+    fn storage(assets: &Assets) -> &Storage<Self> {
+        &assets.sounds
+    }
+
+    fn storage_mut(assets: &mut Assets) -> &mut Storage<Self> {
+        &mut assets.sounds
+    }
+}
+
+impl Assets {
+    pub fn new() -> Self {
+        Assets {
+            images: Storage::default(),
+            sheets: Storage::default(),
+            sounds: Storage::default(),
+            synths: HashMap::new(),
+        }
+    }
+
+    pub async fn load(manifest_path: &str, audio: &Audio, progress: Progress) -> Result<Assets> {
+        let manifest: Manifest =
+            serde_json::from_str(&browser::fetch_text(manifest_path).await?)?;
+
+        let mut assets = Assets::new();
+        assets.register_manifest(&manifest);
+        assets.load_pending(audio, progress).await?;
+        Ok(assets)
+    }
+
+    pub fn register<T: Asset>(&mut self, path: &str) -> Handle<T> {
+        T::storage_mut(self).register(path)
+    }
+
+    pub fn register_manifest(&mut self, manifest: &Manifest) {
+        manifest.images.iter().for_each(|path| {
+            self.register::<HtmlImageElement>(path);
+        });
+        manifest.sheets.iter().for_each(|path| {
+            self.register::<SpriteSheet>(path);
+        });
+        manifest.sounds.iter().for_each(|path| {
+            self.register::<Sound>(path);
+        });
+        manifest.synths.iter().for_each(|(name, params)| {
+            self.register_synth(name, params.clone());
+        });
+    }
+
+    pub fn register_synth(&mut self, name: &str, params: SynthParams) -> Handle<Sound> {
+        self.synths.insert(name.to_string(), params);
+        self.register(name)
+    }
+
+    pub fn handle<T: Asset>(&self, path: &str) -> Result<Handle<T>> {
+        T::storage(self)
+            .find(path)
+            .ok_or_else(|| anyhow!("Asset {} is not registered", path))
+    }
+
+    pub fn get<T: Asset>(&self, handle: Handle<T>) -> Result<Rc<T>> {
+        let storage = T::storage(self);
+        storage.get(handle).ok_or_else(|| {
+            anyhow!(
+                "Asset {} used before it was loaded",
+                storage.paths[handle.id]
+            )
+        })
+    }
+
+    pub async fn load_pending(&mut self, audio: &Audio, progress: Progress) -> Result<()> {
+        let mut loads: Vec<LoadFuture> = vec![];
+        self.images.pending().into_iter().for_each(|(handle, path)| {
+            loads.push(Box::pin(async move {
+                let image = engine::load_image(&path).await?;
+                Ok(Loaded::Image(handle, image))
+            }))
+        });
+        self.sheets.pending().into_iter().for_each(|(handle, path)| {
+            loads.push(Box::pin(async move {
+                let sheet = engine::load_sprite_sheet(&path).await?;
+                Ok(Loaded::Sheet(handle, sheet))
+            }))
+        });
+        self.sounds.pending().into_iter().for_each(|(handle, path)| {
+            let synth = self.synths.get(&path).cloned();
+            loads.push(Box::pin(async move {
+                let sound = match synth {
+                    Some(params) => audio.synthesize(&params)?,
+                    None => audio.load_sound(&path).await?,
+                };
+                Ok(Loaded::Sound(handle, sound))
+            }))
+        });
+
+        progress.start(loads.len());
+        let loaded = join_all(loads.into_iter().map(|load| {
+            let progress = progress.clone();
+            async move {
+                let asset = load.await;
+                progress.advance();
+                asset
+            }
+        }))
+        .await;
+
+        for asset in loaded {
+            match asset? {
+                Loaded::Image(handle, image) => self.images.insert(handle, image),
+                Loaded::Sheet(handle, sheet) => self.sheets.insert(handle, sheet),
+                Loaded::Sound(handle, sound) => self.sounds.insert(handle, sound),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registering_a_path_twice_returns_the_same_handle() {
+        let mut assets = Assets::new();
+        let jump: Handle<Sound> = assets.register("jump.mp3");
+        let music: Handle<Sound> = assets.register("music.mp3");
+
+        assert_eq!(assets.register::<Sound>("jump.mp3"), jump);
+        assert_ne!(jump, music);
+        assert_eq!(assets.handle::<Sound>("music.mp3").ok(), Some(music));
+        assert!(assets.handle::<HtmlImageElement>("jump.mp3").is_err());
+        assert_eq!(assets.sounds.pending().len(), 2);
+        assert!(assets.get(jump).is_err());
+    }
+}
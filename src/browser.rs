@@ -1,9 +1,9 @@
 use anyhow::{anyhow, Result };
-use std::future::Future;
+use std::{fmt, future::Future};
 use wasm_bindgen::{prelude::Closure, JsCast, JsValue, closure::{WasmClosureFnOnce, WasmClosure}};
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{Window, Document, HtmlCanvasElement, CanvasRenderingContext2d, Response, HtmlImageElement, Element, HtmlElement};
-use js_sys::ArrayBuffer;
+use web_sys::{Window, Document, HtmlCanvasElement, CanvasRenderingContext2d, Response, HtmlImageElement, Element, Storage};
+use js_sys::{ArrayBuffer, Promise};
 
 macro_rules! log {
     ($($t:tt)*) => {
@@ -17,6 +17,44 @@ macro_rules! error {
     }
 }
 
+const FETCH_ATTEMPTS: u32 = 3;
+const FETCH_RETRY_DELAY_MS: i32 = 250;
+
+#[derive(Debug)]
+pub enum FetchError {
+    Network { path: String, reason: String },
+    Status { path: String, status: u16 },
+    Body { path: String, reason: String },
+}
+
+impl FetchError {
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FetchError::Network { .. } => true,
+            FetchError::Status { status, .. } => *status >= 500 || *status == 408 || *status == 429,
+            FetchError::Body { .. } => false,
+        }
+    }
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FetchError::Network { path, reason } => {
+                write!(f, "Could not fetch {}: {}", path, reason)
+            }
+            FetchError::Status { path, status } => {
+                write!(f, "Could not fetch {}: HTTP status {}", path, status)
+            }
+            FetchError::Body { path, reason } => {
+                write!(f, "Could not read the response for {}: {}", path, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for FetchError {}
+
 pub fn window() -> Result<Window> {
     web_sys::window().ok_or_else(|| anyhow!("No Window Found"))
 }
@@ -58,82 +96,123 @@ pub async fn fetch_with_str(resource: &str) -> Result<JsValue> {
 }
 
 pub async fn fetch_response(resource: &str) -> Result<Response> {
-    fetch_with_str(resource)
-        .await?
-        .dyn_into()
-        .map_err(|err| anyhow!("Error converting {:#?} to Response", err))
+    let mut delay = FETCH_RETRY_DELAY_MS;
+    let mut attempt = 1;
+    loop {
+        match try_fetch_response(resource).await {
+            Err(err) if err.is_transient() && attempt < FETCH_ATTEMPTS => {
+                log!("{}, retrying in {}ms", err, delay);
+                sleep(delay).await?;
+                delay *= 2;
+                attempt += 1;
+            }
+            result => return result.map_err(anyhow::Error::from),
+        }
+    }
 }
 
-pub async fn fetch_json(json_path: &str) -> Result<JsValue> {
-    let resp: Response = fetch_response(json_path).await?;
+async fn try_fetch_response(resource: &str) -> std::result::Result<Response, FetchError> {
+    let resp: Response = fetch_with_str(resource)
+        .await
+        .and_then(|value| {
+            value
+                .dyn_into()
+                .map_err(|err| anyhow!("Error converting {:#?} to Response", err))
+        })
+        .map_err(|err| FetchError::Network {
+            path: resource.to_string(),
+            reason: err.to_string(),
+        })?;
 
-    JsFuture::from(
-        resp.json()
-            .map_err(|err| anyhow!("Could not get JSON from response {:#?}", err))?,
-    )
+    if resp.ok() {
+        Ok(resp)
+    } else {
+        Err(FetchError::Status {
+            path: resource.to_string(),
+            status: resp.status(),
+        })
+    }
+}
+
+pub async fn fetch_text(resource: &str) -> Result<String> {
+    let resp: Response = fetch_response(resource).await?;
+    let body_error = |err: JsValue| FetchError::Body {
+        path: resource.to_string(),
+        reason: format!("{:?}", err),
+    };
+
+    let text = JsFuture::from(resp.text().map_err(body_error)?)
         .await
-        .map_err(|err| anyhow!("error fetching JSON {:#?}", err))
+        .map_err(body_error)?;
+    Ok(text
+        .as_string()
+        .ok_or_else(|| body_error(text))?)
 }
 
 pub async fn fetch_array_buffer(resource: &str) -> Result<ArrayBuffer> {
+    let body_error = |err: JsValue| FetchError::Body {
+        path: resource.to_string(),
+        reason: format!("{:?}", err),
+    };
     let array_buffer = fetch_response(resource)
         .await?
         .array_buffer()
-        .map_err(|err| anyhow!("Error loading array buffer {:#?}", err))?;
+        .map_err(body_error)?;
 
-    JsFuture::from(array_buffer)
+    Ok(JsFuture::from(array_buffer)
         .await
-        .map_err(|err| anyhow!("Error converting array buffer into a future {:#?}", err))?
+        .map_err(body_error)?
         .dyn_into()
-        .map_err(|err| anyhow!("Error converting raw JsValue to ArrayBuffer {:#?}", err))
+        .map_err(body_error)?)
 }
 
-pub fn new_image() -> Result<HtmlImageElement> {
-    HtmlImageElement::new().map_err(|err| anyhow!("Could not create HtmlImageElement: {:#?}", err))
+pub async fn sleep(millis: i32) -> Result<()> {
+    let window = window()?;
+    let mut scheduled = Ok(0);
+    let promise = Promise::new(&mut |resolve, _reject| {
+        scheduled = window.set_timeout_with_callback_and_timeout_and_arguments_0(&resolve, millis);
+    });
+    scheduled.map_err(|err| anyhow!("Could not schedule timeout {:#?}", err))?;
+
+    JsFuture::from(promise)
+        .await
+        .map(|_| ())
+        .map_err(|err| anyhow!("Timeout failed {:#?}", err))
 }
 
-pub fn draw_ui(html: &str) -> Result<()> {
-    find_ui()?
-        .insert_adjacent_html("afterbegin", html)
-            .map_err(|err| anyhow!("Could not insert html {:#?}", err))
+pub fn local_storage() -> Result<Storage> {
+    window()?
+        .local_storage()
+        .map_err(|err| anyhow!("Could not access local storage {:#?}", err))?
+        .ok_or_else(|| anyhow!("No local storage found"))
 }
 
-pub fn hide_ui() -> Result<()> {
-    let ui = find_ui()?;
+pub fn load_setting(key: &str) -> Result<Option<String>> {
+    local_storage()?
+        .get_item(key)
+        .map_err(|err| anyhow!("Could not read setting {} {:#?}", key, err))
+}
 
-    if let Some(child) = ui.first_child() {
-        ui.remove_child(&child)
-            .map(|_removed_child| ())
-            .map_err(|err| anyhow!("Failed to remove child {:#?}", err))
-            .and_then(|_unit| {
-                canvas()?
-                    .focus()
-                    .map_err(|err| anyhow!("Could not set focus to canvas! {:#?}", err))
-            })
-    } else {
-        Ok(())
-    }
+pub fn save_setting(key: &str, value: &str) -> Result<()> {
+    local_storage()?
+        .set_item(key, value)
+        .map_err(|err| anyhow!("Could not save setting {} {:#?}", key, err))
 }
 
-fn find_ui() -> Result<Element> {
-    document()
-        .and_then(|doc| {
-            doc.get_element_by_id("ui")
-            .ok_or_else(|| anyhow!("UI element not found"))
-        })
+pub fn new_image() -> Result<HtmlImageElement> {
+    HtmlImageElement::new().map_err(|err| anyhow!("Could not create HtmlImageElement: {:#?}", err))
 }
 
-pub fn find_html_element_by_id(id: &str) -> Result<HtmlElement> {
-    document()
-        .and_then(|doc| {
-            doc.get_element_by_id(id)
-            .ok_or_else(|| anyhow!("Element with id {} element not found", id))
-        })
-        .and_then(|element| {
-            element
-                .dyn_into::<HtmlElement>()
-                .map_err(|err| anyhow!("Could not cast into HtmlElement {:#?}", err))
-        })
+pub fn ui() -> Result<Element> {
+    document()?
+        .get_element_by_id("ui")
+        .ok_or_else(|| anyhow!("UI element not found"))
+}
+
+pub fn create_element(tag: &str) -> Result<Element> {
+    document()?
+        .create_element(tag)
+        .map_err(|err| anyhow!("Could not create element {} {:#?}", tag, err))
 }
 
 pub fn closure_once<F, A, R>(fn_once: F) -> Closure<F::FnMut>
@@ -164,4 +243,26 @@ pub fn now() -> Result<f64> {
         .ok_or_else(|| anyhow!("Performance onject not found"))?
         .now()
     )
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_network_and_server_errors_are_retried() {
+        let status = |status| FetchError::Status {
+            path: "rhb.json".to_string(),
+            status,
+        };
+
+        assert!(status(503).is_transient());
+        assert!(status(429).is_transient());
+        assert!(!status(404).is_transient());
+        assert!(FetchError::Network {
+            path: "rhb.json".to_string(),
+            reason: "offline".to_string(),
+        }
+        .is_transient());
+    }
+}
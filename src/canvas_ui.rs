@@ -0,0 +1,97 @@
+use web_sys::HtmlImageElement;
+
+use crate::engine::{Align, Point, Rect, Renderer, TextStyle};
+
+const PANEL_RADIUS: i16 = 10;
+
+#[derive(Clone)]
+pub struct ImageButton {
+    image: HtmlImageElement,
+    normal: Rect,
+    hover: Rect,
+    pressed: Rect,
+}
+
+pub struct Ui<'a> {
+    renderer: &'a Renderer,
+}
+
+impl ImageButton {
+    pub fn new(image: HtmlImageElement, normal: Rect, hover: Rect, pressed: Rect) -> Self {
+        ImageButton {
+            image,
+            normal,
+            hover,
+            pressed,
+        }
+    }
+}
+
+impl<'a> Ui<'a> {
+    pub fn new(renderer: &'a Renderer) -> Self {
+        Ui { renderer }
+    }
+
+    pub fn text(&self, text: &str, position: &Point, style: &TextStyle) {
+        if let Err(err) = self.renderer.draw_styled_text(text, position, style) {
+            error!("Could not draw text {:#?}", err);
+        }
+    }
+
+    pub fn rect(&self, rect: &Rect, colour: &str) {
+        self.renderer.fill_rect(rect, colour);
+    }
+
+    pub fn panel(&self, rect: &Rect, colour: &str) {
+        if let Err(err) = self.renderer.fill_rounded_rect(rect, PANEL_RADIUS, colour) {
+            error!("Could not draw panel {:#?}", err);
+        }
+    }
+
+    pub fn progress_bar(&self, rect: &Rect, progress: f32, background: &str, colour: &str) {
+        self.rect(rect, background);
+        self.rect(
+            &Rect::new(rect.position, filled_width(rect.width, progress), rect.height),
+            colour,
+        );
+    }
+
+    /// Draws the button's normal, hover or pressed frame for the current
+    /// pointer; clicks are read from `KeyState::pointer` during update.
+    pub fn button(&self, button: &ImageButton, rect: &Rect, label: &str, style: &TextStyle) {
+        let pointer = self.renderer.pointer();
+        let frame = match (pointer.is_over(rect), pointer.down) {
+            (true, true) => &button.pressed,
+            (true, false) => &button.hover,
+            (false, _) => &button.normal,
+        };
+        self.renderer.draw_image(&button.image, frame, rect);
+        self.text(
+            label,
+            &Point {
+                x: rect.x() + rect.width / 2,
+                y: rect.y() + rect.height / 2,
+            },
+            &TextStyle {
+                align: Align::Center,
+                ..*style
+            },
+        );
+    }
+}
+
+fn filled_width(width: i16, progress: f32) -> i16 {
+    (f32::from(width) * progress.clamp(0.0, 1.0)).round() as i16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn progress_bars_fill_in_proportion_and_clamp() {
+        assert_eq!(filled_width(100, 0.25), 25);
+        assert_eq!(filled_width(100, 1.5), 100);
+        assert_eq!(filled_width(100, -1.0), 0);
+    }
+}
@@ -1,37 +1,48 @@
 use anyhow::{anyhow, Result };
 use async_trait::async_trait;
-use futures::channel::{oneshot::channel, mpsc::{UnboundedReceiver, unbounded}};
-use web_sys::{HtmlImageElement, CanvasRenderingContext2d, AudioContext, AudioBuffer, HtmlElement};
+use futures::channel::{oneshot::channel, mpsc::{UnboundedReceiver, UnboundedSender, unbounded}};
+use web_sys::{HtmlImageElement, CanvasRenderingContext2d, AudioContext, AudioContextState, AudioBuffer};
 use wasm_bindgen::prelude::*;
 use serde::Deserialize;
-use std::{cell::RefCell, collections::HashMap};
+use rand::prelude::*;
+use std::{cell::{Cell as StdCell, RefCell}, collections::{HashMap, HashSet}};
 use std::rc::Rc;
 use std::sync::Mutex;
 
-use crate::{browser::{self, LoopClosure}, sound};
+use crate::{browser::{self, LoopClosure}, sfx::SoundCue, sound::{self, Bus, Mixer, SynthParams, Voice, Volumes}};
 
 #[async_trait(?Send)]
 pub trait Game {
     async fn initialize(&self) -> Result<Box<dyn Game>>;
     fn update(&mut self, keystate: &KeyState);
     fn draw(&self, renderer: &Renderer);
+
+    fn take_hit_stop(&mut self) -> u8 {
+        0
+    }
+
+    fn audio(&self) -> Option<Audio> {
+        None
+    }
 }
 
 const FRAME_SIZE: f32 = 1.0 / 60.0 * 1000.0;
+const VOLUME_SETTINGS: &str = "walk_the_dog.volumes";
 
 pub struct GameLoop {
     last_frame: f64,
     accumulated_delta: f32,
+    hit_stop: u8,
 }
 type SharedLoopClosure = Rc<RefCell<Option<LoopClosure>>>;
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Point {
     pub x: i16,
     pub y: i16,
 }
 
-#[derive(Default)]
+#[derive(Clone, Copy, Default)]
 pub struct Rect {
     pub position: Point,
     pub width: i16,
@@ -40,6 +51,92 @@ pub struct Rect {
 
 pub struct Renderer {
     context: CanvasRenderingContext2d,
+    pointer: StdCell<Pointer>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct TextStyle {
+    pub font: &'static str,
+    pub size: u8,
+    pub align: Align,
+    pub colour: &'static str,
+}
+
+pub struct Effects {
+    shake: Option<Shake>,
+    hit_stop: u8,
+    flash: Option<Flash>,
+    particles: Particles,
+    sounds: Vec<(SoundCue, Point)>,
+}
+
+#[derive(Clone, Copy)]
+pub enum ParticleLook {
+    Colour(&'static str),
+    Sprite(&'static str),
+}
+
+#[derive(Clone, Copy)]
+pub struct Emitter {
+    pub count: u8,
+    pub velocity_x: (f32, f32),
+    pub velocity_y: (f32, f32),
+    pub gravity: f32,
+    pub lifetime: u16,
+    pub size: i16,
+    pub look: ParticleLook,
+    pub fade: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Particle {
+    x: f32,
+    y: f32,
+    velocity_x: f32,
+    velocity_y: f32,
+    gravity: f32,
+    age: u16,
+    lifetime: u16,
+    size: i16,
+    look: ParticleLook,
+    fade: bool,
+}
+
+pub struct Particles {
+    pool: Vec<Particle>,
+    next: usize,
+    sheet: Option<Rc<SpriteSheet>>,
+}
+
+#[derive(Clone, Copy)]
+pub struct Shake {
+    pub magnitude: f32,
+    pub decay: f32,
+}
+
+#[derive(Clone, Copy)]
+struct Flash {
+    colour: &'static str,
+    remaining: u8,
+    duration: u8,
+}
+
+pub struct Camera {
+    x: f32,
+    y: f32,
+    anchor: Point,
+    smoothing: f32,
+    vertical_margin: Option<i16>,
+    shake_magnitude: f32,
+    shake_decay: f32,
+    shake_offset: Point,
 }
 
 pub struct Image {
@@ -48,9 +145,49 @@ pub struct Image {
     bounding_box: Rect,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum Tiling {
+    Repeat,
+    Single,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Depth {
+    Background,
+    Foreground,
+}
+
+pub struct ParallaxLayer {
+    element: HtmlImageElement,
+    scroll_factor: f32,
+    y: i16,
+    tiling: Tiling,
+    depth: Depth,
+    offset: f32,
+}
+
+pub struct Parallax {
+    layers: Vec<ParallaxLayer>,
+    width: i16,
+}
+
 #[derive(Deserialize, Clone)]
 pub struct Sheet {
+    #[serde(deserialize_with = "deserialize_frames")]
     pub frames: HashMap<String, Cell>,
+    #[serde(default)]
+    pub meta: Option<SheetMeta>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SheetMeta {
+    pub image: String,
+    #[serde(default)]
+    pub size: Option<SheetSize>,
+    #[serde(default)]
+    pub format: Option<String>,
+    #[serde(default)]
+    pub scale: Option<String>,
 }
 
 #[derive(Deserialize, Clone)]
@@ -61,11 +198,58 @@ pub struct SheetRect {
     pub h: i16,
 }
 
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SheetSize {
+    pub w: i16,
+    pub h: i16,
+}
+
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct Pivot {
+    pub x: f32,
+    pub y: f32,
+}
+
 #[derive(Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct Cell {
     pub frame: SheetRect,
+    #[serde(default)]
+    pub rotated: bool,
+    #[serde(default)]
+    pub trimmed: bool,
     pub sprite_source_size: SheetRect,
+    #[serde(default)]
+    pub source_size: Option<SheetSize>,
+    #[serde(default)]
+    pub pivot: Option<Pivot>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Frames {
+    Hash(HashMap<String, Cell>),
+    Array(Vec<NamedCell>),
+}
+
+#[derive(Deserialize)]
+struct NamedCell {
+    filename: String,
+    #[serde(flatten)]
+    cell: Cell,
+}
+
+fn deserialize_frames<'de, D>(deserializer: D) -> std::result::Result<HashMap<String, Cell>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(match Frames::deserialize(deserializer)? {
+        Frames::Hash(frames) => frames,
+        Frames::Array(frames) => frames
+            .into_iter()
+            .map(|named| (named.filename, named.cell))
+            .collect(),
+    })
 }
 
 pub struct SpriteSheet {
@@ -73,13 +257,48 @@ pub struct SpriteSheet {
     image: HtmlImageElement,
 }
 
+#[derive(Clone, Copy, PartialEq)]
+pub enum Playback {
+    Loop,
+    Once,
+    PingPong,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnimationEvent {
+    Looped,
+    Finished,
+}
+
+#[derive(Clone)]
+pub struct Animation {
+    frames: Rc<[String]>,
+    frame_duration: u16,
+    playback: Playback,
+    elapsed: u16,
+}
+
+pub type Animations = Rc<HashMap<&'static str, Animation>>;
+
 pub struct KeyState {
     pressed_keys: HashMap<String, web_sys::KeyboardEvent>,
+    just_pressed: HashSet<String>,
+    pointer: Pointer,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pointer {
+    pub position: Option<Point>,
+    pub down: bool,
+    pub clicked: bool,
 }
 
 #[derive(Clone)]
 pub struct Audio {
     context: AudioContext,
+    mixer: Mixer,
+    volumes: Rc<std::cell::Cell<Volumes>>,
+    listener: Rc<std::cell::Cell<Point>>,
 }
 
 #[derive(Clone)]
@@ -87,6 +306,29 @@ pub struct Sound {
     buffer: AudioBuffer,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AudioState {
+    Suspended,
+    Running,
+    Closed,
+}
+
+pub struct Music {
+    audio: Audio,
+    buffer: AudioBuffer,
+    volume: f32,
+    track: Option<sound::Track>,
+    started_at: f64,
+    paused_at: Option<f64>,
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    Image { path: String },
+    Sheet { path: String, reason: String },
+    Sound { path: String, reason: String },
+}
+
 impl Rect {
     pub const fn new(position: Point, width: i16, height: i16) -> Self {
         Rect {
@@ -112,6 +354,10 @@ impl Rect {
         self.position.x = x
     }
 
+    pub fn set_y(&mut self, y: i16) {
+        self.position.y = y
+    }
+
     pub fn intersects(&self, rect: &Rect) -> bool {
         self.x() < rect.right()
         && self.right() > rect.x()
@@ -126,6 +372,334 @@ impl Rect {
     pub fn bottom(&self) -> i16 {
         self.y() + self.height
     }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        point.x >= self.x() && point.x < self.right() && point.y >= self.y() && point.y < self.bottom()
+    }
+}
+
+impl Particle {
+    const DEAD: Particle = Particle {
+        x: 0.0,
+        y: 0.0,
+        velocity_x: 0.0,
+        velocity_y: 0.0,
+        gravity: 0.0,
+        age: 0,
+        lifetime: 0,
+        size: 0,
+        look: ParticleLook::Colour("#000000"),
+        fade: false,
+    };
+
+    fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+
+    fn alpha(&self) -> f64 {
+        if self.fade {
+            1.0 - f64::from(self.age) / f64::from(self.lifetime)
+        } else {
+            1.0
+        }
+    }
+
+    fn destination(&self) -> Rect {
+        Rect::new_from_x_y(
+            self.x as i16 - self.size / 2,
+            self.y as i16 - self.size / 2,
+            self.size,
+            self.size,
+        )
+    }
+}
+
+impl Particles {
+    pub fn new(capacity: usize) -> Self {
+        Particles {
+            pool: vec![Particle::DEAD; capacity.max(1)],
+            next: 0,
+            sheet: None,
+        }
+    }
+
+    pub fn with_sheet(mut self, sheet: Rc<SpriteSheet>) -> Self {
+        self.sheet = Some(sheet);
+        self
+    }
+
+    pub fn reset(self) -> Self {
+        Particles {
+            pool: vec![Particle::DEAD; self.pool.len()],
+            next: 0,
+            sheet: self.sheet,
+        }
+    }
+
+    pub fn emit(&mut self, position: Point, emitter: &Emitter) {
+        let mut rng = thread_rng();
+        for _ in 0..emitter.count {
+            self.pool[self.next] = Particle {
+                x: position.x.into(),
+                y: position.y.into(),
+                velocity_x: lerp(emitter.velocity_x, rng.gen()),
+                velocity_y: lerp(emitter.velocity_y, rng.gen()),
+                gravity: emitter.gravity,
+                age: 0,
+                lifetime: emitter.lifetime,
+                size: emitter.size,
+                look: emitter.look,
+                fade: emitter.fade,
+            };
+            self.next = (self.next + 1) % self.pool.len();
+        }
+    }
+
+    pub fn shift(&mut self, x: i16) {
+        self.pool
+            .iter_mut()
+            .for_each(|particle| particle.x += f32::from(x));
+    }
+
+    pub fn update(&mut self) {
+        self.pool
+            .iter_mut()
+            .filter(|particle| particle.is_alive())
+            .for_each(|particle| {
+                particle.velocity_y += particle.gravity;
+                particle.x += particle.velocity_x;
+                particle.y += particle.velocity_y;
+                particle.age += 1;
+            });
+    }
+
+    pub fn draw(&self, renderer: &Renderer) {
+        self.pool
+            .iter()
+            .filter(|particle| particle.is_alive())
+            .for_each(|particle| {
+                renderer.set_alpha(particle.alpha());
+                match (particle.look, &self.sheet) {
+                    (ParticleLook::Colour(colour), _) => {
+                        renderer.fill_rect(&particle.destination(), colour)
+                    }
+                    (ParticleLook::Sprite(name), Some(sheet)) => {
+                        if let Some(cell) = sheet.cell(name) {
+                            sheet.draw_cell(renderer, cell, &particle.destination());
+                        }
+                    }
+                    (ParticleLook::Sprite(_), None) => {}
+                }
+            });
+        renderer.set_alpha(1.0);
+    }
+
+    #[cfg(test)]
+    fn alive(&self) -> usize {
+        self.pool.iter().filter(|particle| particle.is_alive()).count()
+    }
+}
+
+fn lerp((from, to): (f32, f32), amount: f32) -> f32 {
+    from + (to - from) * amount
+}
+
+impl Effects {
+    pub fn new(particles: Particles) -> Self {
+        Effects {
+            shake: None,
+            hit_stop: 0,
+            flash: None,
+            particles,
+            sounds: vec![],
+        }
+    }
+
+    pub fn reset(effects: Self) -> Self {
+        Effects::new(effects.particles.reset())
+    }
+
+    pub fn emit(&mut self, position: Point, emitter: &Emitter) {
+        self.particles.emit(position, emitter);
+    }
+
+    pub fn shift(&mut self, x: i16) {
+        self.particles.shift(x);
+        self.sounds
+            .iter_mut()
+            .for_each(|(_, position)| position.x += x);
+    }
+
+    pub fn draw_particles(&self, renderer: &Renderer) {
+        self.particles.draw(renderer);
+    }
+
+    pub fn shake(&mut self, magnitude: f32, decay: f32) {
+        let magnitude = self
+            .shake
+            .map_or(magnitude, |shake| shake.magnitude.max(magnitude));
+        self.shake = Some(Shake { magnitude, decay });
+    }
+
+    pub fn hit_stop(&mut self, frames: u8) {
+        self.hit_stop = self.hit_stop.max(frames);
+    }
+
+    pub fn flash(&mut self, colour: &'static str, frames: u8) {
+        self.flash = Some(Flash {
+            colour,
+            remaining: frames,
+            duration: frames.max(1),
+        });
+    }
+
+    pub fn play_sound(&mut self, cue: SoundCue, position: Point) {
+        self.sounds.push((cue, position));
+    }
+
+    pub fn take_sounds(&mut self) -> Vec<(SoundCue, Point)> {
+        std::mem::take(&mut self.sounds)
+    }
+
+    pub fn take_shake(&mut self) -> Option<Shake> {
+        self.shake.take()
+    }
+
+    pub fn take_hit_stop(&mut self) -> u8 {
+        std::mem::take(&mut self.hit_stop)
+    }
+
+    pub fn update(&mut self) {
+        self.particles.update();
+        self.flash = self.flash.and_then(|flash| match flash.remaining {
+            0 | 1 => None,
+            remaining => Some(Flash {
+                remaining: remaining - 1,
+                ..flash
+            }),
+        });
+    }
+
+    pub fn draw(&self, renderer: &Renderer, screen: &Rect) {
+        if let Some(flash) = self.flash {
+            renderer.fill_overlay(
+                screen,
+                flash.colour,
+                f64::from(flash.remaining) / f64::from(flash.duration),
+            );
+        }
+    }
+}
+
+impl Camera {
+    pub fn new(anchor: Point) -> Self {
+        Camera {
+            x: 0.0,
+            y: 0.0,
+            anchor,
+            smoothing: 1.0,
+            vertical_margin: None,
+            shake_magnitude: 0.0,
+            shake_decay: 0.0,
+            shake_offset: Point::default(),
+        }
+    }
+
+    pub fn smoothing(mut self, smoothing: f32) -> Self {
+        self.smoothing = smoothing.clamp(0.0, 1.0);
+        self
+    }
+
+    pub fn vertical_follow(mut self, margin: i16) -> Self {
+        self.vertical_margin = Some(margin);
+        self
+    }
+
+    pub fn follow(&mut self, target: Point) {
+        let desired_x = (target.x - self.anchor.x) as f32;
+        self.x += (desired_x - self.x) * self.smoothing;
+
+        if let Some(margin) = self.vertical_margin {
+            let desired_y = (target.y - margin).min(0) as f32;
+            self.y += (desired_y - self.y) * self.smoothing;
+        }
+
+        self.update_shake();
+    }
+
+    pub fn shake(&mut self, magnitude: f32, decay: f32) {
+        self.shake_magnitude = self.shake_magnitude.max(magnitude);
+        self.shake_decay = decay.clamp(0.0, 1.0);
+    }
+
+    pub fn shift(&mut self, x: i16) {
+        self.x += x as f32;
+    }
+
+    pub fn position(&self) -> Point {
+        Point {
+            x: self.x as i16 + self.shake_offset.x,
+            y: self.y as i16 + self.shake_offset.y,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn to_screen(&self, point: Point) -> Point {
+        let position = self.position();
+        Point {
+            x: point.x - position.x,
+            y: point.y - position.y,
+        }
+    }
+
+    fn update_shake(&mut self) {
+        if self.shake_magnitude < 0.5 {
+            self.shake_magnitude = 0.0;
+            self.shake_offset = Point::default();
+        } else {
+            let mut rng = thread_rng();
+            let magnitude = self.shake_magnitude as i16;
+            self.shake_offset = Point {
+                x: rng.gen_range(-magnitude..=magnitude),
+                y: rng.gen_range(-magnitude..=magnitude),
+            };
+            self.shake_magnitude *= self.shake_decay;
+        }
+    }
+}
+
+impl Cell {
+    pub fn source(&self) -> Rect {
+        if self.rotated {
+            Rect::new_from_x_y(self.frame.x, self.frame.y, self.frame.h, self.frame.w)
+        } else {
+            Rect::new_from_x_y(self.frame.x, self.frame.y, self.frame.w, self.frame.h)
+        }
+    }
+
+    pub fn destination(&self, position: Point) -> Rect {
+        Rect::new_from_x_y(
+            position.x + self.sprite_source_size.x,
+            position.y + self.sprite_source_size.y,
+            self.frame.w,
+            self.frame.h,
+        )
+    }
+
+    pub fn is_trimmed(&self) -> bool {
+        self.trimmed
+    }
+
+    pub fn source_size(&self) -> SheetSize {
+        self.source_size.unwrap_or(SheetSize {
+            w: self.frame.w,
+            h: self.frame.h,
+        })
+    }
+
+    pub fn pivot(&self) -> Pivot {
+        self.pivot.unwrap_or(Pivot { x: 0.5, y: 0.5 })
+    }
 }
 
 impl SpriteSheet {
@@ -137,15 +711,120 @@ impl SpriteSheet {
         self.sheet.frames.get(name)
     }
 
-    pub fn draw(&self, renderer: &Renderer, source: &Rect, destination: &Rect) {
-        renderer.draw_image(&self.image, source, destination);
+    pub fn sheet(&self) -> &Sheet {
+        &self.sheet
+    }
+
+    pub fn draw_cell(&self, renderer: &Renderer, cell: &Cell, destination: &Rect) {
+        renderer.draw_cell(&self.image, cell, destination);
+    }
+}
+
+impl Animation {
+    pub fn new(sheet: &Sheet, prefix: &str, frame_duration: u16, playback: Playback) -> Self {
+        let mut frames: Vec<(u16, &String)> = sheet
+            .frames
+            .keys()
+            .filter_map(|name| {
+                name.strip_prefix(prefix)
+                    .and_then(|rest| rest.strip_prefix(" ("))
+                    .and_then(|rest| rest.strip_suffix(").png"))
+                    .and_then(|number| number.parse::<u16>().ok())
+                    .map(|number| (number, name))
+            })
+            .collect();
+        frames.sort();
+
+        if frames.is_empty() {
+            error!("No frames found for animation {}", prefix);
+        }
+
+        Animation {
+            frames: frames.into_iter().map(|(_, name)| name.clone()).collect(),
+            frame_duration: frame_duration.max(1),
+            playback,
+            elapsed: 0,
+        }
+    }
+
+    pub fn update(&mut self) -> Option<AnimationEvent> {
+        if self.is_finished() {
+            return None;
+        }
+
+        self.elapsed += 1;
+        match self.playback {
+            Playback::Once if self.is_finished() => Some(AnimationEvent::Finished),
+            Playback::Once => None,
+            Playback::Loop | Playback::PingPong => {
+                self.elapsed %= self.cycle();
+                if self.elapsed == 0 {
+                    Some(AnimationEvent::Looped)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.playback == Playback::Once && self.elapsed >= self.cycle()
+    }
+
+    pub fn elapsed(&self) -> u16 {
+        self.elapsed
+    }
+
+    pub fn frame_name(&self) -> &str {
+        self.frames
+            .get(self.frame_index())
+            .map_or("", |name| name.as_str())
     }
+
+    fn frame_index(&self) -> usize {
+        let length = self.frames.len();
+        let step = (self.elapsed / self.frame_duration) as usize;
+        match self.playback {
+            Playback::Loop => step % length.max(1),
+            Playback::Once => step.min(length.saturating_sub(1)),
+            Playback::PingPong => {
+                let period = (length * 2).saturating_sub(2).max(1);
+                let step = step % period;
+                if step < length {
+                    step
+                } else {
+                    period - step
+                }
+            }
+        }
+    }
+
+    fn cycle(&self) -> u16 {
+        let steps = match self.playback {
+            Playback::Loop | Playback::Once => self.frames.len(),
+            Playback::PingPong => (self.frames.len() * 2).saturating_sub(2),
+        };
+        (steps as u16).max(1) * self.frame_duration
+    }
+}
+
+pub fn animations(sheet: &Sheet, specs: &[(&'static str, u16, Playback)]) -> Animations {
+    Rc::new(
+        specs
+            .iter()
+            .map(|(name, frame_duration, playback)| {
+                (*name, Animation::new(sheet, name, *frame_duration, *playback))
+            })
+            .collect(),
+    )
 }
 
 impl KeyState {
     fn new() -> Self {
         KeyState {
             pressed_keys: HashMap::new(),
+            just_pressed: HashSet::new(),
+            pointer: Pointer::default(),
         }
     }
 
@@ -153,41 +832,263 @@ impl KeyState {
         self.pressed_keys.contains_key(code)
     }
 
+    pub fn just_pressed(&self, code: &str) -> bool {
+        self.just_pressed.contains(code)
+    }
+
+    pub fn pointer(&self) -> Pointer {
+        self.pointer
+    }
+
     fn set_pressed(&mut self, code: &str, event: web_sys::KeyboardEvent) {
+        if !event.repeat() {
+            self.just_pressed.insert(code.into());
+        }
         self.pressed_keys.insert(code.into(), event);
     }
 
     fn set_released(&mut self, code: &str) {
-        self.pressed_keys.remove(code.into());
+        self.pressed_keys.remove(code);
+    }
+
+    fn pointer_moved(&mut self, position: Point) {
+        self.pointer.position = Some(position);
+    }
+
+    fn pointer_pressed(&mut self, position: Point) {
+        self.pointer.position = Some(position);
+        self.pointer.down = true;
+    }
+
+    fn pointer_released(&mut self, position: Point) {
+        self.pointer.position = Some(position);
+        self.pointer.clicked = self.pointer.down;
+        self.pointer.down = false;
+    }
+
+    fn clear_presses(&mut self) {
+        self.just_pressed.clear();
+        self.pointer.clicked = false;
+    }
+}
+
+impl Pointer {
+    pub fn is_over(&self, rect: &Rect) -> bool {
+        matches!(self.position, Some(position) if rect.contains(&position))
+    }
+
+    pub fn clicked(&self, rect: &Rect) -> bool {
+        self.clicked && self.is_over(rect)
     }
 }
 
 impl Audio {
     pub fn new() -> Result<Self> {
+        let context = sound::create_audio_context()?;
+        let mixer = Mixer::new(&context)?;
+        let volumes = load_volumes();
+        mixer.apply(&volumes);
+
         Ok(Audio {
-            context: sound::create_audio_context()?,
+            context,
+            mixer,
+            volumes: Rc::new(std::cell::Cell::new(volumes)),
+            listener: Rc::new(std::cell::Cell::new(Point::default())),
         })
     }
 
+    pub fn volumes(&self) -> Volumes {
+        self.volumes.get()
+    }
+
+    pub fn set_volumes(&self, volumes: Volumes) {
+        self.volumes.set(volumes);
+        self.mixer.apply(&volumes);
+        if let Err(err) = save_volumes(&volumes) {
+            error!("Could not save volume settings {:#?}", err);
+        }
+    }
+
+    pub fn state(&self) -> AudioState {
+        match self.context.state() {
+            AudioContextState::Running => AudioState::Running,
+            AudioContextState::Closed => AudioState::Closed,
+            _ => AudioState::Suspended,
+        }
+    }
+
+    pub fn resume(&self) {
+        if self.state() != AudioState::Suspended {
+            return;
+        }
+
+        let context = self.context.clone();
+        browser::spawn_local(async move {
+            if let Err(err) = sound::resume_audio_context(&context).await {
+                error!("{:#?}", err);
+            }
+        });
+    }
+
+    pub fn toggle_mute(&self) {
+        let mut volumes = self.volumes();
+        volumes.muted = !volumes.muted;
+        self.set_volumes(volumes);
+    }
+
     pub async fn load_sound(&self, filename: &str) -> Result<Sound> {
         let array_buffer = browser::fetch_array_buffer(filename).await?;
 
-        let audio_buffer = sound::decode_audio_data(&self.context, &array_buffer).await?;
+        let audio_buffer = sound::decode_audio_data(&self.context, &array_buffer)
+            .await
+            .map_err(|err| AssetError::Sound {
+                path: filename.to_string(),
+                reason: err.to_string(),
+            })?;
 
         Ok(Sound {
             buffer: audio_buffer,
         })
     }
 
-    pub fn play_sound(&self, sound: &Sound) -> Result<()> {
-        sound::play_sound(&self.context, &sound.buffer, sound::LOOPING::NO)
+    pub fn synthesize(&self, params: &SynthParams) -> Result<Sound> {
+        let sample_rate = self.context.sample_rate();
+        let mut samples = sound::synthesize(params, sample_rate);
+
+        Ok(Sound {
+            buffer: sound::create_buffer(&self.context, &mut samples, sample_rate)?,
+        })
+    }
+
+    pub fn set_listener(&self, position: Point) {
+        self.listener.set(position);
+    }
+
+    pub fn play_sound(
+        &self,
+        sound: &Sound,
+        volume: f32,
+        rate: f32,
+        position: Option<Point>,
+    ) -> Result<()> {
+        // Effects started while suspended would all fire at once on resume, so drop them.
+        if self.state() != AudioState::Running {
+            return Ok(());
+        }
+
+        let (pan, attenuation) = position.map_or((0.0, 1.0), |position| {
+            let listener = self.listener.get();
+            sound::spatialize(
+                (position.x - listener.x) as f32,
+                (position.y - listener.y) as f32,
+            )
+        });
+        if attenuation <= 0.0 {
+            return Ok(());
+        }
+
+        sound::play_sound(
+            &self.context,
+            &sound.buffer,
+            self.mixer.bus(Bus::Effects),
+            Voice {
+                volume: volume * attenuation,
+                rate,
+                pan,
+            },
+            sound::Looping::No,
+        )
+        .map(|_track| ())
     }
 
-    pub fn play_looping_sound(&self, sound: &Sound) -> Result<()> {
-        sound::play_sound(&self.context, &sound.buffer, sound::LOOPING::YES)
+    // Music is scheduled even while suspended and starts once the context resumes.
+    pub fn play_music(&self, sound: &Sound, volume: f32, fade_in: u16) -> Result<Music> {
+        let mut music = Music {
+            audio: self.clone(),
+            buffer: sound.buffer.clone(),
+            volume: 0.0,
+            track: None,
+            started_at: 0.0,
+            paused_at: Some(0.0),
+        };
+        music.resume()?;
+        music.fade_to(volume, fade_in)?;
+        Ok(music)
     }
 }
 
+impl Sound {
+    pub fn duration_in_frames(&self, rate: f32) -> u16 {
+        let seconds = self.buffer.duration() / rate.max(0.01) as f64;
+        (seconds * 1000.0 / FRAME_SIZE as f64).ceil() as u16
+    }
+}
+
+impl Music {
+    pub fn fade_to(&mut self, volume: f32, frames: u16) -> Result<()> {
+        self.volume = volume;
+        match &self.track {
+            Some(track) => track.fade_to(&self.audio.context, volume, frames_to_seconds(frames)),
+            None => Ok(()),
+        }
+    }
+
+    pub fn pause(&mut self) -> Result<()> {
+        if let Some(track) = self.track.take() {
+            let played = self.audio.context.current_time() - self.started_at;
+            self.paused_at = Some(played % self.buffer.duration());
+            track.stop(&self.audio.context, 0.0)?;
+        }
+        Ok(())
+    }
+
+    pub fn resume(&mut self) -> Result<()> {
+        if let Some(offset) = self.paused_at.take() {
+            self.track = Some(sound::play_sound_from(
+                &self.audio.context,
+                &self.buffer,
+                self.audio.mixer.bus(Bus::Music),
+                Voice {
+                    volume: self.volume,
+                    rate: 1.0,
+                    pan: 0.0,
+                },
+                sound::Looping::Yes,
+                offset,
+            )?);
+            self.started_at = self.audio.context.current_time() - offset;
+        }
+        Ok(())
+    }
+
+    pub fn stop(mut self, fade_out: u16) -> Result<()> {
+        let seconds = frames_to_seconds(fade_out);
+        match self.track.take() {
+            Some(track) => {
+                track.fade_to(&self.audio.context, 0.0, seconds)?;
+                track.stop(&self.audio.context, seconds)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+fn frames_to_seconds(frames: u16) -> f64 {
+    frames as f64 * FRAME_SIZE as f64 / 1000.0
+}
+
+fn load_volumes() -> Volumes {
+    browser::load_setting(VOLUME_SETTINGS)
+        .ok()
+        .flatten()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+fn save_volumes(volumes: &Volumes) -> Result<()> {
+    browser::save_setting(VOLUME_SETTINGS, &serde_json::to_string(volumes)?)
+}
+
 impl Image {
     pub fn new(element: HtmlImageElement, position: Point) -> Self {
         // let destination_box = Rect {
@@ -213,7 +1114,7 @@ impl Image {
     // }
 
     pub fn bounding_box(&self) -> &Rect {
-        &&self.bounding_box
+        &self.bounding_box
     }
 
     pub fn move_horizontally(&mut self, distance: i16) {
@@ -235,6 +1136,96 @@ impl Image {
     }
 }
 
+impl ParallaxLayer {
+    pub fn new(element: HtmlImageElement, scroll_factor: f32, y: i16, tiling: Tiling) -> Self {
+        ParallaxLayer {
+            element,
+            scroll_factor,
+            y,
+            tiling,
+            depth: Depth::Background,
+            offset: 0.0,
+        }
+    }
+
+    pub fn in_foreground(mut self) -> Self {
+        self.depth = Depth::Foreground;
+        self
+    }
+
+    fn image_width(&self) -> f32 {
+        self.element.width() as f32
+    }
+
+    fn scroll(&mut self, velocity: i16, viewport_width: i16) {
+        self.offset += velocity as f32 * self.scroll_factor;
+
+        let period = match self.tiling {
+            Tiling::Repeat => self.image_width(),
+            Tiling::Single => self.image_width() + viewport_width as f32,
+        };
+        if period > 0.0 {
+            if self.offset <= -self.image_width() {
+                self.offset += period;
+            } else if self.offset > viewport_width as f32 {
+                self.offset -= period;
+            }
+        }
+    }
+
+    fn draw(&self, renderer: &Renderer, viewport_width: i16) {
+        let width = self.image_width();
+        match self.tiling {
+            Tiling::Single => {
+                renderer.draw_entire_image(
+                    &self.element,
+                    &Point {
+                        x: self.offset as i16,
+                        y: self.y,
+                    },
+                );
+            }
+            Tiling::Repeat if width > 0.0 => {
+                let mut x = self.offset % width;
+                if x > 0.0 {
+                    x -= width;
+                }
+                while x < viewport_width as f32 {
+                    renderer.draw_entire_image(
+                        &self.element,
+                        &Point {
+                            x: x as i16,
+                            y: self.y,
+                        },
+                    );
+                    x += width;
+                }
+            }
+            Tiling::Repeat => {}
+        }
+    }
+}
+
+impl Parallax {
+    pub fn new(layers: Vec<ParallaxLayer>, width: i16) -> Self {
+        Parallax { layers, width }
+    }
+
+    pub fn scroll(&mut self, velocity: i16) {
+        let width = self.width;
+        self.layers
+            .iter_mut()
+            .for_each(|layer| layer.scroll(velocity, width));
+    }
+
+    pub fn draw(&self, renderer: &Renderer, depth: Depth) {
+        self.layers
+            .iter()
+            .filter(|layer| layer.depth == depth)
+            .for_each(|layer| layer.draw(renderer, self.width));
+    }
+}
+
 impl Renderer {
     pub fn clear(&self, rect: &Rect) {
         self.context.clear_rect(
@@ -245,10 +1236,23 @@ impl Renderer {
         );
     }
 
+    pub fn draw_from(&self, camera: &Camera, draw: impl FnOnce(&Renderer)) {
+        let position = camera.position();
+        self.context.save();
+        if let Err(err) = self
+            .context
+            .translate(-f64::from(position.x), -f64::from(position.y))
+        {
+            error!("Could not apply camera transform {:#?}", err);
+        }
+        draw(self);
+        self.context.restore();
+    }
+
     pub fn draw_image(&self, image: &HtmlImageElement, frame: &Rect, destination: &Rect) {
         self.context
             .draw_image_with_html_image_element_and_sw_and_sh_and_dx_and_dy_and_dw_and_dh(
-                &image,
+                image,
                 frame.x().into(),
                 frame.y().into(),
                 frame.width.into(),
@@ -261,6 +1265,35 @@ impl Renderer {
             .expect("Drawing is throwing exceptions! Unrecoverable error.");
     }
 
+    pub fn draw_cell(&self, image: &HtmlImageElement, cell: &Cell, destination: &Rect) {
+        if !cell.rotated {
+            self.draw_image(image, &cell.source(), destination);
+            return;
+        }
+
+        // TexturePacker stores rotated frames turned 90 degrees clockwise.
+        let source = cell.source();
+        self.context.save();
+        self.context
+            .translate(
+                destination.x() as f64 + destination.width as f64 / 2.0,
+                destination.y() as f64 + destination.height as f64 / 2.0,
+            )
+            .and_then(|_| self.context.rotate(-std::f64::consts::FRAC_PI_2))
+            .expect("Rotating is throwing exceptions! Unrecoverable error.");
+        self.draw_image(
+            image,
+            &source,
+            &Rect::new_from_x_y(
+                -destination.height / 2,
+                -destination.width / 2,
+                destination.height,
+                destination.width,
+            ),
+        );
+        self.context.restore();
+    }
+
     pub fn draw_entire_image(&self, image: &HtmlImageElement, position: &Point) {
         self.context
             .draw_image_with_html_image_element(image, position.x.into(), position.y.into())
@@ -280,9 +1313,103 @@ impl Renderer {
         self.context.stroke();
     }
 
-    #[allow(dead_code)]
+    pub fn fill_rect(&self, rect: &Rect, colour: &str) {
+        self.context.set_fill_style(&JsValue::from_str(colour));
+        self.context.fill_rect(
+            rect.x().into(),
+            rect.y().into(),
+            rect.width.into(),
+            rect.height.into(),
+        );
+    }
+
+    pub fn set_alpha(&self, alpha: f64) {
+        self.context.set_global_alpha(alpha.clamp(0.0, 1.0));
+    }
+
+    pub fn fill_overlay(&self, rect: &Rect, colour: &str, alpha: f64) {
+        self.context.save();
+        self.context.set_global_alpha(alpha.clamp(0.0, 1.0));
+        self.fill_rect(rect, colour);
+        self.context.restore();
+    }
+
+    pub fn fill_circle(&self, center: &Point, radius: i16, colour: &str) {
+        self.context.set_fill_style(&JsValue::from_str(colour));
+        self.context.begin_path();
+        if let Err(err) = self.context.arc(
+            center.x.into(),
+            center.y.into(),
+            radius.into(),
+            0.0,
+            std::f64::consts::PI * 2.0,
+        ) {
+            error!("Could not draw circle {:#?}", err);
+        }
+        self.context.fill();
+    }
+
+    pub fn stroke_circle(&self, center: &Point, radius: i16, colour: &str) {
+        self.context.set_stroke_style(&JsValue::from_str(colour));
+        self.context.begin_path();
+        if let Err(err) = self.context.arc(
+            center.x.into(),
+            center.y.into(),
+            radius.into(),
+            0.0,
+            std::f64::consts::PI * 2.0,
+        ) {
+            error!("Could not draw circle {:#?}", err);
+        }
+        self.context.stroke();
+    }
+
+    pub fn pointer(&self) -> Pointer {
+        self.pointer.get()
+    }
+
+    pub fn fill_rounded_rect(&self, rect: &Rect, radius: i16, colour: &str) -> Result<()> {
+        let (left, top) = (f64::from(rect.x()), f64::from(rect.y()));
+        let (right, bottom) = (f64::from(rect.right()), f64::from(rect.bottom()));
+        let radius = f64::from(radius.min(rect.width / 2).min(rect.height / 2).max(0));
+
+        self.context.set_fill_style(&JsValue::from_str(colour));
+        self.context.begin_path();
+        self.context.move_to(left + radius, top);
+        self.context
+            .arc_to(right, top, right, bottom, radius)
+            .and_then(|_| self.context.arc_to(right, bottom, left, bottom, radius))
+            .and_then(|_| self.context.arc_to(left, bottom, left, top, radius))
+            .and_then(|_| self.context.arc_to(left, top, right, top, radius))
+            .map_err(|err| anyhow!("Error drawing rounded rect {:#?}", err))?;
+        self.context.close_path();
+        self.context.fill();
+        Ok(())
+    }
+
+    /// Draws text vertically centred on `location`.
+    pub fn draw_styled_text(&self, text: &str, location: &Point, style: &TextStyle) -> Result<()> {
+        self.context.save();
+        self.context
+            .set_font(&format!("{}px \"{}\"", style.size, style.font));
+        self.context.set_fill_style(&JsValue::from_str(style.colour));
+        self.context.set_text_align(match style.align {
+            Align::Left => "left",
+            Align::Center => "center",
+            Align::Right => "right",
+        });
+        self.context.set_text_baseline("middle");
+        let result = self
+            .context
+            .fill_text(text, location.x.into(), location.y.into())
+            .map_err(|err| anyhow!("Error filling text {:#?}", err));
+        self.context.restore();
+        result
+    }
+
     pub fn draw_text(&self, text: &str, location: &Point) -> Result <()> {
         self.context.set_font("16px serif");
+        self.context.set_fill_style(&JsValue::from_str("#000000"));
         self.context
             .fill_text(text, location.x.into(), location.y.into())
             .map_err(|err| anyhow!("Error filling text {:#?}", err))?;
@@ -292,17 +1419,19 @@ impl Renderer {
 
 impl GameLoop {
     pub async fn start(game: impl Game + 'static) -> Result<()> {
-        let mut keyevent_receiver = prepare_input()?;
-
         let mut game = game.initialize().await?;
 
+        let mut keyevent_receiver = prepare_input(game.audio())?;
+
         let mut game_loop = GameLoop {
             last_frame: browser::now()?,
             accumulated_delta: 0.0,
+            hit_stop: 0,
         };
 
         let renderer = Renderer {
             context: browser::context()?,
+            pointer: StdCell::new(Pointer::default()),
         };
 
         let f: SharedLoopClosure = Rc::new(RefCell::new(None));
@@ -318,11 +1447,18 @@ impl GameLoop {
 
                 game_loop.accumulated_delta += frame_time as f32;
                 while game_loop.accumulated_delta > FRAME_SIZE {
-                    game.update(&keystate);
+                    if game_loop.hit_stop > 0 {
+                        game_loop.hit_stop -= 1;
+                    } else {
+                        game.update(&keystate);
+                        keystate.clear_presses();
+                        game_loop.hit_stop = game.take_hit_stop();
+                    }
                     game_loop.accumulated_delta -= FRAME_SIZE;
                 }
                 game_loop.last_frame = perf;
 
+                renderer.pointer.set(keystate.pointer());
                 game.draw(&renderer);
 
                 if cfg!(debug_assertions) {
@@ -331,7 +1467,9 @@ impl GameLoop {
                     }
                 }
 
-                browser::request_animation_frame(f.borrow().as_ref().unwrap());
+                if let Err(err) = browser::request_animation_frame(f.borrow().as_ref().unwrap()) {
+                    error!("Could not request the next frame {:#?}", err);
+                }
             })
         );
 
@@ -345,6 +1483,43 @@ impl GameLoop {
     }
 }
 
+impl std::fmt::Display for AssetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AssetError::Image { path } => write!(f, "Could not load image {}", path),
+            AssetError::Sheet { path, reason } => {
+                write!(f, "Could not read sprite sheet {}: {}", path, reason)
+            }
+            AssetError::Sound { path, reason } => {
+                write!(f, "Could not decode sound {}: {}", path, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssetError {}
+
+pub async fn load_sprite_sheet(json_path: &str) -> Result<SpriteSheet> {
+    let json = browser::fetch_text(json_path).await?;
+    let sheet = serde_json::from_str::<Sheet>(&json)
+        .map_err(|err| AssetError::Sheet {
+            path: json_path.to_string(),
+            reason: err.to_string(),
+        })?;
+    let image = match &sheet.meta {
+        Some(meta) => load_image(&meta.image).await?,
+        None => {
+            return Err(AssetError::Sheet {
+                path: json_path.to_string(),
+                reason: "no meta image".to_string(),
+            }
+            .into())
+        }
+    };
+
+    Ok(SpriteSheet::new(sheet, image))
+}
+
 pub async fn load_image(source: &str) -> Result<HtmlImageElement> {
     let image = browser::new_image()?;
 
@@ -356,13 +1531,18 @@ pub async fn load_image(source: &str) -> Result<HtmlImageElement> {
     let success_callback = browser::closure_once(move || {
         if let Some(success_tx) = success_tx.lock().ok()
             .and_then(|mut opt| opt.take()) {
-                success_tx.send(Ok(()));
+                if success_tx.send(Ok(())).is_err() {
+                    error!("Image loaded after its loader was dropped");
+                }
             };
     });
-    let error_callback: Closure<dyn FnMut(JsValue)> = browser::closure_once(move |err| {
+    let path = source.to_string();
+    let error_callback: Closure<dyn FnMut(JsValue)> = browser::closure_once(move |_err| {
         if let Some(error_tx) = error_tx.lock().ok()
             .and_then(|mut opt| opt.take()) {
-                error_tx.send(Err(anyhow!("Error Loading Image: {:#?}", err)));
+                if error_tx.send(Err(AssetError::Image { path }.into())).is_err() {
+                    error!("Image failed after its loader was dropped");
+                }
             }
     });
 
@@ -379,40 +1559,89 @@ pub async fn load_image(source: &str) -> Result<HtmlImageElement> {
 enum KeyPress {
     KeyUp(web_sys::KeyboardEvent),
     KeyDown(web_sys::KeyboardEvent),
+    PointerMove(Point),
+    PointerDown(Point),
+    PointerUp(Point),
 }
 
-fn prepare_input() -> Result<UnboundedReceiver<KeyPress>> {
+fn prepare_input(audio: Option<Audio>) -> Result<UnboundedReceiver<KeyPress>> {
     let (keydown_sender, keyevent_receiver) = unbounded();
     let keydown_sender = Rc::new(RefCell::new(keydown_sender));
     let keyup_sender = Rc::clone(&keydown_sender);
+    let pointer_sender = Rc::clone(&keydown_sender);
+    let pointer_audio = audio.clone();
 
     let onkeydown = browser::closure_wrap(
         Box::new(move |keycode: web_sys::KeyboardEvent| {
-            keydown_sender
+            if let Some(audio) = &audio {
+                audio.resume();
+            }
+            if let Err(err) = keydown_sender
                 .borrow_mut()
-                .start_send(KeyPress::KeyDown(keycode));
+                .start_send(KeyPress::KeyDown(keycode)) {
+                error!("Could not send key down event {:#?}", err);
+            }
         }) as Box<dyn FnMut(web_sys::KeyboardEvent)>
     );
 
     let onkeyup = browser::closure_wrap(
         Box::new(move |keycode: web_sys::KeyboardEvent| {
-            keyup_sender
+            if let Err(err) = keyup_sender
                 .borrow_mut()
-                .start_send(KeyPress::KeyUp(keycode));
+                .start_send(KeyPress::KeyUp(keycode)) {
+                error!("Could not send key up event {:#?}", err);
+            }
         }) as Box<dyn FnMut(web_sys::KeyboardEvent)>
     );
 
     browser::window()?
         .set_onkeydown(Some(onkeydown.as_ref().unchecked_ref()));
+    let onpointerdown = browser::closure_wrap(Box::new(move || {
+        if let Some(audio) = &pointer_audio {
+            audio.resume();
+        }
+    }) as Box<dyn FnMut()>);
+
     browser::window()?
         .set_onkeyup(Some(onkeyup.as_ref().unchecked_ref()));
+    browser::window()?
+        .set_onpointerdown(Some(onpointerdown.as_ref().unchecked_ref()));
 
     onkeydown.forget();
     onkeyup.forget();
+    onpointerdown.forget();
+
+    let onpointermove = pointer_closure(Rc::clone(&pointer_sender), KeyPress::PointerMove);
+    let onpointerdown = pointer_closure(Rc::clone(&pointer_sender), KeyPress::PointerDown);
+    let onpointerup = pointer_closure(pointer_sender, KeyPress::PointerUp);
+
+    let canvas = browser::canvas()?;
+    canvas.set_onpointermove(Some(onpointermove.as_ref().unchecked_ref()));
+    canvas.set_onpointerdown(Some(onpointerdown.as_ref().unchecked_ref()));
+    canvas.set_onpointerup(Some(onpointerup.as_ref().unchecked_ref()));
+
+    onpointermove.forget();
+    onpointerdown.forget();
+    onpointerup.forget();
 
     Ok(keyevent_receiver)
 }
 
+fn pointer_closure(
+    sender: Rc<RefCell<UnboundedSender<KeyPress>>>,
+    press: fn(Point) -> KeyPress,
+) -> Closure<dyn FnMut(web_sys::PointerEvent)> {
+    browser::closure_wrap(Box::new(move |event: web_sys::PointerEvent| {
+        let position = Point {
+            x: event.offset_x() as i16,
+            y: event.offset_y() as i16,
+        };
+        if let Err(err) = sender.borrow_mut().start_send(press(position)) {
+            error!("Could not send pointer event {:#?}", err);
+        }
+    }) as Box<dyn FnMut(web_sys::PointerEvent)>)
+}
+
 fn process_input(state: &mut KeyState, keyevent_receiver: &mut UnboundedReceiver<KeyPress>) {
     loop {
         match keyevent_receiver.try_next() {
@@ -421,21 +1650,14 @@ fn process_input(state: &mut KeyState, keyevent_receiver: &mut UnboundedReceiver
             Ok(Some(evt)) => match evt {
                 KeyPress::KeyUp(evt) => state.set_released(&evt.code()),
                 KeyPress::KeyDown(evt) => state.set_pressed(&evt.code(), evt),
+                KeyPress::PointerMove(position) => state.pointer_moved(position),
+                KeyPress::PointerDown(position) => state.pointer_pressed(position),
+                KeyPress::PointerUp(position) => state.pointer_released(position),
             },
         };
     }
 }
 
-pub fn add_click_handler(elem: HtmlElement) -> UnboundedReceiver<()> {
-    let (mut click_sender, click_receiver) = unbounded();
-    let on_click = browser::closure_wrap(Box::new(move || {
-        click_sender.start_send(());
-    }) as Box<dyn FnMut()>);
-    elem.set_onclick(Some(on_click.as_ref().unchecked_ref()));
-    on_click.forget();
-    click_receiver
-}
-
 unsafe fn draw_frame_rate(renderer: &Renderer, frame_time: f64) {
     static mut FRAMES_COUNTED: i32 = 0;
     static mut TOTAL_FRAME_TIME: f64 = 0.0;
@@ -475,6 +1697,210 @@ mod tests {
             width: 100,
         };
 
-        assert_eq!(rect2.intersects(&rect1), true);
+        assert!(rect2.intersects(&rect1));
+    }
+
+    #[test]
+    fn animations_order_frames_and_finish_once() {
+        let cell = Cell {
+            frame: SheetRect { x: 0, y: 0, w: 1, h: 1 },
+            rotated: false,
+            trimmed: false,
+            sprite_source_size: SheetRect { x: 0, y: 0, w: 1, h: 1 },
+            source_size: None,
+            pivot: None,
+        };
+        let sheet = Sheet {
+            frames: ["Run (2).png", "Run (10).png", "Run (1).png", "Runner (1).png"]
+                .iter()
+                .map(|name| (name.to_string(), cell.clone()))
+                .collect(),
+            meta: None,
+        };
+
+        let mut ping_pong = Animation::new(&sheet, "Run", 1, Playback::PingPong);
+        let names: Vec<String> = (0..5)
+            .map(|_| {
+                let name = ping_pong.frame_name().to_string();
+                ping_pong.update();
+                name
+            })
+            .collect();
+        assert_eq!(
+            names,
+            ["Run (1).png", "Run (2).png", "Run (10).png", "Run (2).png", "Run (1).png"]
+        );
+
+        let mut once = Animation::new(&sheet, "Run", 2, Playback::Once);
+        let events: Vec<Option<AnimationEvent>> = (0..7).map(|_| once.update()).collect();
+        assert_eq!(events[5], Some(AnimationEvent::Finished));
+        assert_eq!(events[6], None);
+        assert!(once.is_finished());
+        assert_eq!(once.frame_name(), "Run (10).png");
+    }
+
+    #[test]
+    fn sheets_load_from_the_texture_packer_hash_format() {
+        let sheet: Sheet = serde_json::from_str(
+            r#"{
+                "frames": {
+                    "Run (1).png": {
+                        "frame": {"x": 0, "y": 0, "w": 40, "h": 60},
+                        "rotated": false,
+                        "trimmed": true,
+                        "spriteSourceSize": {"x": 5, "y": 8, "w": 40, "h": 60},
+                        "sourceSize": {"w": 50, "h": 70}
+                    }
+                },
+                "meta": {
+                    "image": "rhb.png",
+                    "format": "RGBA8888",
+                    "size": {"w": 512, "h": 256},
+                    "scale": "1"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let cell = &sheet.frames["Run (1).png"];
+        let destination = cell.destination(Point { x: 100, y: 200 });
+        assert_eq!(destination.position, Point { x: 105, y: 208 });
+        assert_eq!((destination.width, destination.height), (40, 60));
+        assert!(cell.is_trimmed());
+        assert_eq!(cell.source_size(), SheetSize { w: 50, h: 70 });
+        assert_eq!(cell.pivot(), Pivot { x: 0.5, y: 0.5 });
+
+        let meta = sheet.meta.unwrap();
+        assert_eq!(meta.image, "rhb.png");
+        assert_eq!(meta.format.as_deref(), Some("RGBA8888"));
+        assert_eq!(meta.size, Some(SheetSize { w: 512, h: 256 }));
+        assert_eq!(meta.scale.as_deref(), Some("1"));
+    }
+
+    #[test]
+    fn sheets_load_from_the_texture_packer_array_format_with_rotated_cells() {
+        let sheet: Sheet = serde_json::from_str(
+            r#"{
+                "frames": [
+                    {
+                        "filename": "Idle (1).png",
+                        "frame": {"x": 10, "y": 20, "w": 30, "h": 40},
+                        "rotated": true,
+                        "trimmed": true,
+                        "spriteSourceSize": {"x": 2, "y": 4, "w": 30, "h": 40},
+                        "sourceSize": {"w": 36, "h": 48},
+                        "pivot": {"x": 0.25, "y": 1.0}
+                    },
+                    {
+                        "filename": "Idle (2).png",
+                        "frame": {"x": 50, "y": 20, "w": 30, "h": 40},
+                        "spriteSourceSize": {"x": 0, "y": 0, "w": 30, "h": 40}
+                    }
+                ],
+                "meta": {
+                    "image": "dog.png",
+                    "format": "RGBA4444",
+                    "size": {"w": 128, "h": 64},
+                    "scale": "0.5"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let rotated = &sheet.frames["Idle (1).png"];
+        let source = rotated.source();
+        assert_eq!(source.position, Point { x: 10, y: 20 });
+        assert_eq!((source.width, source.height), (40, 30));
+        assert_eq!(rotated.destination(Point::default()).position, Point { x: 2, y: 4 });
+        assert!(rotated.is_trimmed());
+        assert_eq!(rotated.source_size(), SheetSize { w: 36, h: 48 });
+        assert_eq!(rotated.pivot(), Pivot { x: 0.25, y: 1.0 });
+
+        let upright = &sheet.frames["Idle (2).png"];
+        assert_eq!((upright.source().width, upright.source().height), (30, 40));
+        assert!(!upright.is_trimmed());
+        assert_eq!(upright.source_size(), SheetSize { w: 30, h: 40 });
+        assert_eq!(upright.pivot(), Pivot { x: 0.5, y: 0.5 });
+
+        let meta = sheet.meta.unwrap();
+        assert_eq!(meta.image, "dog.png");
+        assert_eq!(meta.format.as_deref(), Some("RGBA4444"));
+        assert_eq!(meta.size, Some(SheetSize { w: 128, h: 64 }));
+        assert_eq!(meta.scale.as_deref(), Some("0.5"));
+    }
+
+    #[test]
+    fn particles_expire_after_their_lifetime() {
+        let mut particles = Particles::new(4);
+        let emitter = Emitter {
+            count: 6,
+            velocity_x: (-1.0, 1.0),
+            velocity_y: (0.0, 0.0),
+            gravity: 0.5,
+            lifetime: 2,
+            size: 2,
+            look: ParticleLook::Colour("#FFFFFF"),
+            fade: true,
+        };
+
+        particles.emit(Point { x: 0, y: 0 }, &emitter);
+        assert_eq!(particles.alive(), 4);
+
+        particles.update();
+        particles.update();
+        assert_eq!(particles.alive(), 0);
+    }
+
+    #[test]
+    fn shifting_effects_moves_particles_and_pending_sounds() {
+        let mut effects = Effects::new(Particles::new(1));
+        let emitter = Emitter {
+            count: 1,
+            velocity_x: (0.0, 0.0),
+            velocity_y: (0.0, 0.0),
+            gravity: 0.0,
+            lifetime: 10,
+            size: 2,
+            look: ParticleLook::Colour("#FFFFFF"),
+            fade: false,
+        };
+        effects.emit(Point { x: 10050, y: 20 }, &emitter);
+        effects.play_sound(SoundCue::Collect, Point { x: 10050, y: 20 });
+
+        effects.shift(-10000);
+
+        assert_eq!(effects.particles.pool[0].x, 50.0);
+        assert_eq!(
+            effects.take_sounds(),
+            vec![(SoundCue::Collect, Point { x: 50, y: 20 })]
+        );
+    }
+
+    #[test]
+    fn camera_keeps_target_at_anchor() {
+        let mut camera = Camera::new(Point { x: -20, y: 0 });
+
+        camera.follow(Point { x: 100, y: 475 });
+
+        assert_eq!(camera.position().x, 120);
+        assert_eq!(camera.to_screen(Point { x: 100, y: 475 }).x, -20);
+    }
+
+    #[test]
+    fn a_click_hits_the_button_under_the_pointer_for_one_update() {
+        let button = Rect::new_from_x_y(10, 10, 80, 30);
+        let mut keystate = KeyState::new();
+
+        keystate.pointer_moved(Point { x: 20, y: 20 });
+        assert!(keystate.pointer().is_over(&button));
+        keystate.pointer_pressed(Point { x: 20, y: 20 });
+        assert!(!keystate.pointer().clicked(&button));
+
+        keystate.pointer_released(Point { x: 20, y: 20 });
+        assert!(keystate.pointer().clicked(&button));
+        assert!(!keystate.pointer().clicked(&Rect::new_from_x_y(100, 10, 80, 30)));
+
+        keystate.clear_presses();
+        assert!(!keystate.pointer().clicked(&button));
     }
 }
\ No newline at end of file
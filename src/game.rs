@@ -1,24 +1,194 @@
-use std::rc::Rc;
-use futures::channel::mpsc::UnboundedReceiver;
+use std::{collections::VecDeque, rc::Rc};
+use futures::channel::oneshot::{channel, Receiver};
 use rand::prelude::*;
 
-use crate::{engine::{self, Game, Renderer, Rect, KeyState, Point, Image, Sheet, Cell, SpriteSheet, Audio, Sound}, browser, segments::{stone_and_platform, platform_and_stone}};
+use crate::{assets::{Assets, Handle, Progress}, engine::{Game, Renderer, Rect, KeyState, Align, TextStyle, Point, Image, Cell, SpriteSheet, Audio, AudioState, Music, Sound, Parallax, ParallaxLayer, Tiling, Depth, Camera, Effects, Particles, Emitter, ParticleLook, Animation, Animations, Playback}, browser, tween::{Easing, Tween, TweenEvent}, segments::{stone_and_platform, platform_and_stone, stone_with_collectibles, platform_with_power_up, moving_platform_and_stone, crumbling_platform_and_stone, one_way_platform, walker_on_platform, hopper_and_stone, flyer}};
+use crate::sfx::{SoundCue, SoundEvent, SoundEvents, WebAudio};
+use crate::canvas_ui::{ImageButton, Ui};
+use crate::ui::{self, Button, Label, List, Panel, Screen, Widget};
+use crate::high_scores::HighScores;
+use crate::sound::next_volume;
 use crate::state_machine::*;
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use web_sys::HtmlImageElement;
 
 pub const HEIGHT: i16 = 600;
+pub const WIDTH: i16 = 600;
 const TIMELINE_MINIMUM: i16 = 1000;
+const MUTE_KEY: &str = "KeyM";
+const SOUND_LOCKED_HINT: &str = "Press any key for sound";
+const SOUND_LOCKED_POSITION: Point = Point { x: 10, y: HEIGHT - 10 };
 const OBSTACLE_BUFFER: i16 = 20;
 
+const GAME_TITLE: &str = "Walk the Dog";
+const BACK_KEY: &str = "Escape";
+const REDUCE_MOTION_SETTING: &str = "walk_the_dog.reduce_motion";
+const CONTROLS: &str = "Run: Right arrow   Jump: Space   Slide: Down arrow   Mute: M";
+const CREDITS: [&str; 4] = [
+    "Red Hat Boy art by gameart2d.com",
+    "Font and buttons by Kenney (kenney.nl)",
+    "Sound effects synthesized in game",
+    "Based on Game Development with Rust and WebAssembly",
+];
+
+const HIT_SHAKE: f32 = 12.0;
+const SHIELD_SHAKE: f32 = 5.0;
+const STOMP_SHAKE: f32 = 4.0;
+const SHAKE_DECAY: f32 = 0.85;
+const HIT_STOP: u8 = 6;
+const STOMP_HIT_STOP: u8 = 3;
+const HIT_FLASH: &str = "#FFFFFF";
+const HIT_FLASH_FRAMES: u8 = 10;
+
+const PARTICLE_CAPACITY: usize = 512;
+const SLIDE_DUST_INTERVAL: u16 = 3;
+const JUMP_DUST: Emitter = Emitter {
+    count: 8,
+    velocity_x: (-2.0, 1.0),
+    velocity_y: (-2.0, 0.0),
+    gravity: 0.1,
+    lifetime: 20,
+    size: 6,
+    look: ParticleLook::Sprite("Dust.png"),
+    fade: true,
+};
+const LANDING_PUFF: Emitter = Emitter {
+    count: 12,
+    velocity_x: (-3.0, 3.0),
+    velocity_y: (-1.5, 0.0),
+    gravity: 0.05,
+    lifetime: 24,
+    size: 8,
+    look: ParticleLook::Sprite("Dust.png"),
+    fade: true,
+};
+const SLIDE_DUST: Emitter = Emitter {
+    count: 2,
+    velocity_x: (-3.0, -1.0),
+    velocity_y: (-1.0, 0.0),
+    gravity: 0.05,
+    lifetime: 15,
+    size: 5,
+    look: ParticleLook::Colour("#C8B08A"),
+    fade: true,
+};
+const SPARKLE: Emitter = Emitter {
+    count: 10,
+    velocity_x: (-3.0, 3.0),
+    velocity_y: (-3.0, 3.0),
+    gravity: 0.0,
+    lifetime: 25,
+    size: 10,
+    look: ParticleLook::Sprite("Star.png"),
+    fade: true,
+};
+
+const ASSET_MANIFEST: &str = "assets.json";
+const LOADING_BAR_BACKGROUND: &str = "#CCCCCC";
+const LOADING_BAR_COLOUR: &str = "#3B7D23";
+
+const JUMP_SOUND: &str = "SFX_Jump_23.mp3";
+const WALKING_MUSIC: &str = "background_song.mp3";
+const MENU_MUSIC: &str = "menu_song.wav";
+const MUSIC_VOLUME: f32 = 1.0;
+const DUCKED_MUSIC_VOLUME: f32 = 0.3;
+const MUSIC_FADE_FRAMES: u16 = 30;
+const MUSIC_CROSSFADE_FRAMES: u16 = 90;
+
+const UI_FONT: &str = "Ken Future";
+const HUD_TEXT: TextStyle = TextStyle {
+    font: UI_FONT,
+    size: 14,
+    align: Align::Left,
+    colour: "#FFFFFF",
+};
+const HUD_PANEL_COLOUR: &str = "rgba(0, 0, 0, 0.4)";
+const HUD_BAR_BACKGROUND: &str = "rgba(255, 255, 255, 0.3)";
+const BUTTON_TEXT: TextStyle = TextStyle {
+    font: UI_FONT,
+    size: 14,
+    align: Align::Center,
+    colour: "#333333",
+};
+const BUTTON_IMAGE: &str = "Button.svg";
+const BUTTON_NORMAL: Rect = Rect::new_from_x_y(72, 60, 82, 33);
+const BUTTON_HOVER: Rect = Rect::new_from_x_y(158, 60, 82, 33);
+const BUTTON_PRESSED: Rect = Rect::new_from_x_y(244, 60, 82, 33);
+const PAUSE_BUTTON: Rect = Rect::new_from_x_y(WIDTH - 92, 10, 82, 33);
+const PAUSE_OVERLAY_COLOUR: &str = "#000000";
+const PAUSE_OVERLAY_ALPHA: f64 = 0.5;
+const PAUSE_PANEL: Rect = Rect::new_from_x_y(WIDTH / 2 - 130, 160, 260, 290);
+const PAUSE_PANEL_COLOUR: &str = "rgba(0, 0, 0, 0.6)";
+const PAUSE_TITLE: TextStyle = TextStyle {
+    font: UI_FONT,
+    size: 36,
+    align: Align::Center,
+    colour: "#FFFFFF",
+};
+const RESUME_BUTTON: Rect = Rect::new_from_x_y(WIDTH / 2 - 82, 260, 164, 66);
+const QUIT_BUTTON: Rect = Rect::new_from_x_y(WIDTH / 2 - 82, 350, 164, 66);
+const PROMPT_TEXT: TextStyle = TextStyle {
+    font: UI_FONT,
+    size: 18,
+    align: Align::Center,
+    colour: "#FFFFFF",
+};
+const PROMPT_PANEL: Rect = Rect::new_from_x_y(WIDTH / 2 - 160, HEIGHT - 80, 320, 40);
+
+const HUD_HIDDEN_OFFSET: f32 = -40.0;
+const HUD_POP_IN_FRAMES: u16 = 30;
+const SCORE_ROLL_UP_DELAY: u16 = 30;
+const SCORE_ROLL_UP_FRAMES: u16 = 90;
+const SCORE_POSITION: Point = Point { x: WIDTH / 2 - 40, y: HEIGHT / 2 - 60 };
+
+const CAMERA_ANCHOR: Point = Point { x: -20, y: 0 };
+const CAMERA_SMOOTHING: f32 = 0.2;
+const CAMERA_VERTICAL_MARGIN: i16 = 150;
+const REBASE_DISTANCE: i16 = 10000;
+
+const BACKGROUND_SCROLL: f32 = 1.0;
+const CLOUD_SCROLL: f32 = 0.25;
+const CLOUD_HEIGHT: i16 = 0;
+const FOREGROUND_CLOUD_SCROLL: f32 = 1.5;
+const FOREGROUND_CLOUD_HEIGHT: i16 = -60;
+
+const COLLECTIBLE_RADIUS: i16 = 10;
+const COLLECTIBLE_COLOUR: &str = "#FFD700";
+const POWER_UP_RADIUS: i16 = 16;
+const POWER_UPS: [PowerUp; 3] = [PowerUp::Shield, PowerUp::Magnet, PowerUp::DoubleJump];
+const SHIELD_RADIUS: i16 = 60;
+const MAGNET_RANGE: i16 = 250;
+const MAGNET_SPEED: i16 = 8;
+const FOLLOW_DELAY: usize = 12;
+
+const CRUMBLE_DELAY: u16 = 30;
+const CRUMBLE_SHAKE: i16 = 2;
+const PLATFORM_GRAVITY: i16 = 1;
+const PLATFORM_TERMINAL_VELOCITY: i16 = 20;
+
+const ENEMY_GRAVITY: i16 = 1;
+const ENEMY_TERMINAL_VELOCITY: i16 = 20;
+const ENEMY_DEFEAT_BOUNCE: i16 = -8;
+const ENEMY_FRAME_DURATION: u16 = 3;
+const HOPPER_JUMP_SPEED: i16 = -16;
+const HOPPER_REST: u16 = 45;
+const ENEMY_NEAR_DISTANCE: i16 = 500;
+const FLYER_DRIFT: i16 = -1;
+
 pub struct WalkTheDog {
     machine: Option<WalkTheDogStateMachine>,
+    mute_held: bool,
 }
 
 enum WalkTheDogStateMachine {
+    Loading(Loading),
+    Title(WalkTheDogState<Title>),
+    Settings(WalkTheDogState<Settings>),
+    Info(WalkTheDogState<Info>),
     Ready(WalkTheDogState<Ready>),
     Walking(WalkTheDogState<Walking>),
+    Paused(WalkTheDogState<Paused>),
     GameOver(WalkTheDogState<GameOver>),
 }
 
@@ -27,25 +197,130 @@ struct WalkTheDogState<T> {
     walk: Walk,
 }
 
+struct Loading {
+    audio: Audio,
+    progress: Progress,
+    assets: Receiver<Result<Assets>>,
+    error: Option<String>,
+    error_screen: Option<Screen<()>>,
+}
+
+#[derive(Clone, Copy)]
+enum TitleAction {
+    Play,
+    Settings,
+    HighScores,
+    Credits,
+}
+
+#[derive(Clone, Copy)]
+enum SettingsAction {
+    Master,
+    Music,
+    Effects,
+    Mute,
+    ReduceMotion,
+    Back,
+}
+
+#[derive(Clone, Copy)]
+enum GameOverAction {
+    NewGame,
+    MainMenu,
+}
+
+struct Title {
+    menu: Option<Screen<TitleAction>>,
+}
+
+struct Settings {
+    menu: Option<Screen<SettingsAction>>,
+}
+
+struct Info {
+    menu: Option<Screen<()>>,
+    from: TitleAction,
+}
+
 struct Ready;
 struct Walking;
+struct Paused;
 struct GameOver {
-    new_game_event: UnboundedReceiver<()>,
+    menu: Option<Screen<GameOverAction>>,
+    score: Tween,
+    high_score: bool,
 }
 
 pub struct Walk {
     boy: RedHatBoy,
-    backgrounds: [Image; 2],
+    dog: Dog,
+    parallax: Parallax,
+    camera: Camera,
+    effects: Effects,
+    hud: Tween,
     obstacles: Vec<Box<dyn Obstacle>>,
-    obstacle_sheet: Rc<SpriteSheet>,
-    stone: HtmlImageElement,
+    assets: Rc<Assets>,
+    audio: Audio,
+    music: MusicController,
+    sound_events: SoundEvents,
+    walking_music: Handle<Sound>,
+    menu_music: Handle<Sound>,
+    obstacle_sheet: Handle<SpriteSheet>,
+    enemy_sheet: Handle<SpriteSheet>,
+    stone: Handle<HtmlImageElement>,
     timeline: i16,
+    reduce_motion: bool,
+    button: ImageButton,
+    tutorial: Tutorial,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Tutorial {
+    Run,
+    Jump,
+    Slide,
+    Done,
+}
+
+struct MusicController {
+    audio: Audio,
+    plan: MusicPlan,
+    music: Option<Music>,
+}
+
+#[derive(Default)]
+struct MusicPlan {
+    track: Option<Handle<Sound>>,
+    paused: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum MusicChange {
+    Crossfade(Handle<Sound>),
+    Resume,
+    FadeTo(f32),
+    Pause,
 }
 
 pub struct RedHatBoy {
     state_machine: RedHatBoyStateMachine,
-    sprite_sheet: Sheet,
-    image: HtmlImageElement,
+    animations: Animations,
+    sprite_sheet: Rc<SpriteSheet>,
+}
+
+pub struct Dog {
+    state_machine: DogStateMachine,
+    animations: Animations,
+    sprite_sheet: Rc<SpriteSheet>,
+    trail: VecDeque<Footstep>,
+}
+
+#[derive(Clone, Copy)]
+struct Footstep {
+    x: i16,
+    ground: i16,
+    moving: bool,
+    jumping: bool,
 }
 
 pub struct Platform {
@@ -53,45 +328,527 @@ pub struct Platform {
     bounding_boxes: Vec<Rect>,
     sprites: Vec<Cell>,
     position: Point,
+    motion: Motion,
+    one_way: bool,
+}
+
+enum Motion {
+    Static,
+    Oscillating(Tween),
+    Crumbling {
+        countdown: Option<u16>,
+        fall_speed: i16,
+    },
 }
 
 pub struct Barrier {
     image: Image,
 }
 
+pub struct Collectible {
+    position: Point,
+    collected: bool,
+}
+
+pub struct PowerUpPickup {
+    power_up: PowerUp,
+    position: Point,
+    collected: bool,
+}
+
+pub struct Enemy {
+    sheet: Rc<SpriteSheet>,
+    behaviour: EnemyBehaviour,
+    position: Point,
+    animation: Animation,
+    defeated: Option<i16>,
+    announced: bool,
+}
+
+#[derive(Clone, Copy)]
+pub enum EnemyBehaviour {
+    Walker {
+        patrol: i16,
+        speed: i16,
+        travelled: i16,
+    },
+    Hopper {
+        ground: i16,
+        velocity: i16,
+        rest: u16,
+    },
+    Flyer {
+        origin: i16,
+        amplitude: i16,
+        period: u16,
+        tick: u16,
+    },
+}
+
 pub trait Obstacle {
-    fn check_intersection(&self, boy: &mut RedHatBoy);
+    fn check_intersection(&mut self, boy: &mut RedHatBoy, effects: &mut Effects);
     fn draw(&self, renderer: &Renderer);
     fn move_horizontally(&mut self, x: i16);
     fn right(&self) -> i16;
+
+    fn update(&mut self) {}
+
+    fn is_finished(&self) -> bool {
+        false
+    }
 }
 
 impl GameOver {
-    fn new_game_pressed(&mut self) -> bool {
-        matches!(self.new_game_event.try_next(), Ok(Some(())))
+    fn new(collected: u16, high_score: bool) -> Self {
+        GameOver {
+            menu: None,
+            score: Tween::new(0.0, collected as f32, SCORE_ROLL_UP_FRAMES, Easing::CubicOut)
+                .delay(SCORE_ROLL_UP_DELAY),
+            high_score,
+        }
+    }
+
+    fn update(&mut self) -> Option<GameOverAction> {
+        if self.score.update() == Some(TweenEvent::Completed) {
+            self.menu = show_menu(
+                Panel::new().child(
+                    List::new()
+                        .item(Button::new("New Game", GameOverAction::NewGame))
+                        .item(Button::new("Main Menu", GameOverAction::MainMenu)),
+                ),
+                0,
+            );
+        }
+
+        next_action(&mut self.menu)
+    }
+
+    fn close_menu(&mut self) {
+        close_menu(self.menu.take());
+    }
+
+    fn draw(&self, renderer: &Renderer) {
+        if let Err(err) = renderer.draw_text(
+            &format!("Collected {}", self.score.value().round() as u16),
+            &SCORE_POSITION,
+        ) {
+            error!("Could not draw score {:#?}", err);
+        }
+        if self.high_score && self.score.is_complete() {
+            if let Err(err) = renderer.draw_text(
+                "New high score!",
+                &Point {
+                    x: SCORE_POSITION.x,
+                    y: SCORE_POSITION.y + 24,
+                },
+            ) {
+                error!("Could not draw high score {:#?}", err);
+            }
+        }
     }
 }
 
-impl WalkTheDogStateMachine {
-    fn new(walk: Walk) -> Self {
-        WalkTheDogStateMachine::Ready(WalkTheDogState::new(walk))
+fn show_menu<E: Clone + 'static>(widget: impl Into<Widget<E>>, focus: usize) -> Option<Screen<E>> {
+    match ui::show_focused(widget, focus) {
+        Ok(menu) => Some(menu),
+        Err(err) => {
+            error!("Could not show menu {:#?}", err);
+            None
+        }
+    }
+}
+
+fn next_action<E>(menu: &mut Option<Screen<E>>) -> Option<E> {
+    menu.as_mut().and_then(Screen::try_next_event)
+}
+
+fn close_menu<E>(menu: Option<Screen<E>>) {
+    if let Some(menu) = menu {
+        if let Err(err) = menu.close() {
+            error!("Could not close menu {:#?}", err);
+        }
     }
+}
+
+fn title_menu() -> Panel<TitleAction> {
+    Panel::new()
+        .with_class("menu")
+        .child(Label::new(GAME_TITLE))
+        .child(
+            List::new()
+                .item(Button::new("Play", TitleAction::Play))
+                .item(Button::new("Settings", TitleAction::Settings))
+                .item(Button::new("High Scores", TitleAction::HighScores))
+                .item(Button::new("Credits", TitleAction::Credits)),
+        )
+}
+
+fn settings_menu(walk: &Walk) -> Panel<SettingsAction> {
+    let volumes = walk.audio.volumes();
+    let percent = |volume: f32| (volume * 100.0).round() as u8;
+    Panel::new()
+        .with_class("menu")
+        .child(Label::new("Settings"))
+        .child(
+            List::new()
+                .item(Button::new(
+                    &format!("Master {}%", percent(volumes.master)),
+                    SettingsAction::Master,
+                ))
+                .item(Button::new(
+                    &format!("Music {}%", percent(volumes.music)),
+                    SettingsAction::Music,
+                ))
+                .item(Button::new(
+                    &format!("Effects {}%", percent(volumes.effects)),
+                    SettingsAction::Effects,
+                ))
+                .item(Button::new(
+                    if volumes.muted { "Sound Off" } else { "Sound On" },
+                    SettingsAction::Mute,
+                ))
+                .item(Button::new(
+                    if walk.reduce_motion {
+                        "Motion Reduced"
+                    } else {
+                        "Motion Full"
+                    },
+                    SettingsAction::ReduceMotion,
+                ))
+                .item(Button::new("Back", SettingsAction::Back)),
+        )
+        .child(Label::new(CONTROLS))
+}
+
+fn high_scores_menu(high_scores: &HighScores) -> Panel<()> {
+    let scores = if high_scores.scores().is_empty() {
+        List::new().item(Label::new("No scores yet"))
+    } else {
+        high_scores
+            .scores()
+            .iter()
+            .enumerate()
+            .fold(List::new(), |list, (rank, score)| {
+                list.item(Label::new(&format!("{}. {}", rank + 1, score)))
+            })
+    };
+    Panel::new()
+        .with_class("menu")
+        .child(Label::new("High Scores"))
+        .child(scores)
+        .child(Button::new("Back", ()))
+}
+
+fn credits_menu() -> Panel<()> {
+    Panel::new()
+        .with_class("menu")
+        .child(Label::new("Credits"))
+        .child(
+            CREDITS
+                .iter()
+                .fold(List::new(), |list, credit| list.item(Label::new(credit))),
+        )
+        .child(Button::new("Back", ()))
+}
 
+impl WalkTheDogStateMachine {
     fn update(self, keystate: &KeyState) -> Self {
         match self {
+            WalkTheDogStateMachine::Loading(state) => state.update().into(),
+            WalkTheDogStateMachine::Title(state) => state.update().into(),
+            WalkTheDogStateMachine::Settings(state) => state.update(keystate).into(),
+            WalkTheDogStateMachine::Info(state) => state.update(keystate).into(),
             WalkTheDogStateMachine::Ready(state) => state.update(keystate).into(),
             WalkTheDogStateMachine::Walking(state) => state.update(keystate).into(),
+            WalkTheDogStateMachine::Paused(state) => state.update(keystate).into(),
             WalkTheDogStateMachine::GameOver(state) => state.update().into(),
         }
     }
 
+    fn audio(&self) -> &Audio {
+        match self {
+            WalkTheDogStateMachine::Loading(state) => &state.audio,
+            WalkTheDogStateMachine::Title(state) => &state.walk.audio,
+            WalkTheDogStateMachine::Settings(state) => &state.walk.audio,
+            WalkTheDogStateMachine::Info(state) => &state.walk.audio,
+            WalkTheDogStateMachine::Ready(state) => &state.walk.audio,
+            WalkTheDogStateMachine::Walking(state) => &state.walk.audio,
+            WalkTheDogStateMachine::Paused(state) => &state.walk.audio,
+            WalkTheDogStateMachine::GameOver(state) => &state.walk.audio,
+        }
+    }
+
+    fn take_hit_stop(&mut self) -> u8 {
+        match self {
+            WalkTheDogStateMachine::Loading(_) => 0,
+            WalkTheDogStateMachine::Title(state) => state.walk.effects.take_hit_stop(),
+            WalkTheDogStateMachine::Settings(state) => state.walk.effects.take_hit_stop(),
+            WalkTheDogStateMachine::Info(state) => state.walk.effects.take_hit_stop(),
+            WalkTheDogStateMachine::Ready(state) => state.walk.effects.take_hit_stop(),
+            WalkTheDogStateMachine::Walking(state) => state.walk.effects.take_hit_stop(),
+            WalkTheDogStateMachine::Paused(_) => 0,
+            WalkTheDogStateMachine::GameOver(state) => state.walk.effects.take_hit_stop(),
+        }
+    }
+
     fn draw(&self, renderer: &Renderer) {
         match self {
-            WalkTheDogStateMachine::Ready(state) => state.draw(renderer),
-            WalkTheDogStateMachine::Walking(state) => state.draw(renderer),
-            WalkTheDogStateMachine::GameOver(state) => state.draw(renderer),
+            WalkTheDogStateMachine::Loading(state) => state.draw(renderer),
+            WalkTheDogStateMachine::Title(state) => state.draw(renderer),
+            WalkTheDogStateMachine::Settings(state) => state.draw(renderer),
+            WalkTheDogStateMachine::Info(state) => state.draw(renderer),
+            WalkTheDogStateMachine::Ready(state) => {
+                state.draw(renderer);
+                state.walk.draw_tutorial(renderer);
+            }
+            WalkTheDogStateMachine::Walking(state) => {
+                state.draw(renderer);
+                state.walk.draw_tutorial(renderer);
+                Ui::new(renderer).button(&state.walk.button, &PAUSE_BUTTON, "Pause", &BUTTON_TEXT);
+            }
+            WalkTheDogStateMachine::Paused(state) => {
+                state.draw(renderer);
+                state._state.draw(renderer, &state.walk.button);
+            }
+            WalkTheDogStateMachine::GameOver(state) => {
+                state.draw(renderer);
+                state._state.draw(renderer);
+            }
+        }
+    }
+}
+
+enum LoadingEndState {
+    Complete(Box<WalkTheDogState<Title>>),
+    Continue(Loading),
+}
+
+impl Loading {
+    fn start(audio: Audio) -> Self {
+        let progress = Progress::default();
+        let (sender, receiver) = channel();
+
+        let loader_audio = audio.clone();
+        let loader_progress = progress.clone();
+        browser::spawn_local(async move {
+            let assets = Assets::load(ASSET_MANIFEST, &loader_audio, loader_progress).await;
+            if sender.send(assets).is_err() {
+                error!("Loading screen closed before assets were ready");
+            }
+        });
+
+        Loading {
+            audio,
+            progress,
+            assets: receiver,
+            error: None,
+            error_screen: None,
+        }
+    }
+
+    fn update(mut self) -> LoadingEndState {
+        if self.retry_pressed() {
+            return LoadingEndState::Continue(self.retry());
+        }
+
+        match self.assets.try_recv() {
+            Ok(Some(Ok(assets))) => match Walk::new(Rc::new(assets), self.audio.clone()) {
+                Ok(walk) => LoadingEndState::Complete(Box::new(WalkTheDogState::<Title>::new(walk))),
+                Err(err) => self.fail(err),
+            },
+            Ok(Some(Err(err))) => self.fail(err),
+            Ok(None) | Err(_) => LoadingEndState::Continue(self),
+        }
+    }
+
+    fn fail(mut self, err: anyhow::Error) -> LoadingEndState {
+        error!("Could not load assets {:#?}", err);
+        let message = format!("Could not load assets: {}", err);
+
+        let screen = Panel::new()
+            .with_class("error")
+            .child(Label::new(&message))
+            .child(Button::new("Retry", ()));
+        match ui::show(screen) {
+            Ok(screen) => self.error_screen = Some(screen),
+            Err(err) => {
+                error!("Could not show error screen {:#?}", err);
+            }
+        }
+        self.error = Some(message);
+        LoadingEndState::Continue(self)
+    }
+
+    fn retry_pressed(&mut self) -> bool {
+        self.error_screen
+            .as_mut()
+            .and_then(|screen| screen.try_next_event())
+            .is_some()
+    }
+
+    fn retry(mut self) -> Loading {
+        if let Some(screen) = self.error_screen.take() {
+            if let Err(err) = screen.close() {
+                error!("Could not close error screen {:#?}", err);
+            }
+        }
+        Loading::start(self.audio)
+    }
+
+    fn draw(&self, renderer: &Renderer) {
+        const BAR_WIDTH: i16 = 300;
+        const BAR_HEIGHT: i16 = 20;
+        let bar_x = (WIDTH - BAR_WIDTH) / 2;
+        let bar_y = (HEIGHT - BAR_HEIGHT) / 2;
+
+        let message = match &self.error {
+            Some(error) => error.as_str(),
+            None => "Loading...",
+        };
+        if let Err(err) = renderer.draw_text(message, &Point { x: bar_x, y: bar_y - 10 }) {
+            error!("Could not draw text {:#?}", err);
+        }
+
+        renderer.fill_rect(
+            &Rect::new_from_x_y(bar_x, bar_y, BAR_WIDTH, BAR_HEIGHT),
+            LOADING_BAR_BACKGROUND,
+        );
+        renderer.fill_rect(
+            &Rect::new_from_x_y(
+                bar_x,
+                bar_y,
+                (BAR_WIDTH as f32 * self.progress.fraction()) as i16,
+                BAR_HEIGHT,
+            ),
+            LOADING_BAR_COLOUR,
+        );
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+enum TitleEndState {
+    Play(WalkTheDogState<Ready>),
+    Settings(WalkTheDogState<Settings>),
+    Info(WalkTheDogState<Info>),
+    Continue(WalkTheDogState<Title>),
+}
+
+impl WalkTheDogState<Title> {
+    fn new(mut walk: Walk) -> Self {
+        walk.music.play(&walk.assets, walk.menu_music);
+        WalkTheDogState::<Title>::focused_on(walk, TitleAction::Play)
+    }
+
+    fn focused_on(walk: Walk, action: TitleAction) -> Self {
+        WalkTheDogState {
+            _state: Title {
+                menu: show_menu(title_menu(), action as usize),
+            },
+            walk,
+        }
+    }
+
+    fn update(mut self) -> TitleEndState {
+        self.walk.idle();
+        match next_action(&mut self._state.menu) {
+            Some(TitleAction::Play) => {
+                TitleEndState::Play(WalkTheDogState::<Ready>::new(self.leave()))
+            }
+            Some(TitleAction::Settings) => {
+                TitleEndState::Settings(WalkTheDogState::<Settings>::new(self.leave()))
+            }
+            Some(action) => {
+                TitleEndState::Info(WalkTheDogState::<Info>::new(self.leave(), action))
+            }
+            None => TitleEndState::Continue(self),
+        }
+    }
+
+    fn leave(self) -> Walk {
+        close_menu(self._state.menu);
+        self.walk
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
+enum MenuEndState<T> {
+    Back(WalkTheDogState<Title>),
+    Continue(WalkTheDogState<T>),
+}
+
+impl WalkTheDogState<Settings> {
+    fn new(walk: Walk) -> Self {
+        WalkTheDogState {
+            _state: Settings {
+                menu: show_menu(settings_menu(&walk), 0),
+            },
+            walk,
+        }
+    }
+
+    fn update(mut self, keystate: &KeyState) -> MenuEndState<Settings> {
+        self.walk.idle();
+        match next_action(&mut self._state.menu) {
+            Some(SettingsAction::Back) => MenuEndState::Back(self.back()),
+            Some(action) => {
+                self.change(action);
+                MenuEndState::Continue(self)
+            }
+            None if keystate.just_pressed(BACK_KEY) => MenuEndState::Back(self.back()),
+            None => MenuEndState::Continue(self),
+        }
+    }
+
+    fn change(&mut self, action: SettingsAction) {
+        let mut volumes = self.walk.audio.volumes();
+        match action {
+            SettingsAction::Master => volumes.master = next_volume(volumes.master),
+            SettingsAction::Music => volumes.music = next_volume(volumes.music),
+            SettingsAction::Effects => volumes.effects = next_volume(volumes.effects),
+            SettingsAction::Mute => volumes.muted = !volumes.muted,
+            SettingsAction::ReduceMotion => self.walk.toggle_reduce_motion(),
+            SettingsAction::Back => {}
+        }
+        if volumes != self.walk.audio.volumes() {
+            self.walk.audio.set_volumes(volumes);
+        }
+
+        let focus = self._state.menu.as_ref().map_or(0, Screen::focused);
+        close_menu(self._state.menu.take());
+        self._state.menu = show_menu(settings_menu(&self.walk), focus);
+    }
+
+    fn back(self) -> WalkTheDogState<Title> {
+        close_menu(self._state.menu);
+        WalkTheDogState::<Title>::focused_on(self.walk, TitleAction::Settings)
+    }
+}
+
+impl WalkTheDogState<Info> {
+    fn new(walk: Walk, from: TitleAction) -> Self {
+        let menu = match from {
+            TitleAction::HighScores => show_menu(high_scores_menu(&HighScores::load()), 0),
+            _ => show_menu(credits_menu(), 0),
+        };
+        WalkTheDogState {
+            _state: Info { menu, from },
+            walk,
+        }
+    }
+
+    fn update(mut self, keystate: &KeyState) -> MenuEndState<Info> {
+        self.walk.idle();
+        if next_action(&mut self._state.menu).is_some() || keystate.just_pressed(BACK_KEY) {
+            MenuEndState::Back(self.back())
+        } else {
+            MenuEndState::Continue(self)
         }
     }
+
+    fn back(self) -> WalkTheDogState<Title> {
+        close_menu(self._state.menu);
+        WalkTheDogState::<Title>::focused_on(self.walk, self._state.from)
+    }
 }
 
 enum ReadyEndState {
@@ -108,7 +865,7 @@ impl WalkTheDogState<Ready> {
     }
 
     fn update(mut self, keystate: &KeyState) -> ReadyEndState {
-        self.walk.boy.update();
+        self.walk.idle();
         if keystate.is_pressed("ArrowRight") {
             ReadyEndState::Complete(self.start_running())
         } else {
@@ -118,6 +875,7 @@ impl WalkTheDogState<Ready> {
 
     fn start_running(mut self) -> WalkTheDogState<Walking> {
         self.run_right();
+        self.walk.music.play(&self.walk.assets, self.walk.walking_music);
         WalkTheDogState {
             _state: Walking,
             walk: self.walk,
@@ -131,61 +889,89 @@ impl WalkTheDogState<Ready> {
 
 enum WalkingEndState {
     Complete(WalkTheDogState<GameOver>),
+    Pause(WalkTheDogState<Paused>),
     Continue(WalkTheDogState<Walking>)
 }
 
 impl WalkTheDogState<Walking> {
-    fn end_game(self) -> WalkTheDogState<GameOver> {
-        let receiver = browser::draw_ui("<button id='new_game'>New Game</button>")
-            .and_then(|_unit| browser::find_html_element_by_id("new_game"))
-            .map(|element| engine::add_click_handler(element))
-            .unwrap();
+    fn end_game(mut self) -> WalkTheDogState<GameOver> {
+        self.walk.music.duck(&self.walk.assets);
+        let collected = self.walk.boy.collected();
+        let mut high_scores = HighScores::load();
+        let high_score = high_scores.record(collected);
+        if high_score {
+            if let Err(err) = high_scores.save() {
+                error!("Could not save high scores {:#?}", err);
+            }
+        }
+        WalkTheDogState {
+            _state: GameOver::new(collected, high_score),
+            walk: self.walk,
+        }
+    }
 
+    fn pause(mut self) -> WalkTheDogState<Paused> {
+        self.walk.music.pause(&self.walk.assets);
         WalkTheDogState {
-            _state: GameOver {
-                new_game_event: receiver,
-            },
+            _state: Paused,
             walk: self.walk,
         }
     }
 
     fn update(mut self, keystate: &KeyState) -> WalkingEndState {
+        if keystate.just_pressed(BACK_KEY) || keystate.pointer().clicked(&PAUSE_BUTTON) {
+            return WalkingEndState::Pause(self.pause());
+        }
+
+        let was_jumping = self.walk.boy.jumping();
+
         if keystate.is_pressed("ArrowDown") {
             self.walk.boy.slide();
         }
         if keystate.is_pressed("Space") {
             self.walk.boy.jump();
         }
+        if keystate.just_pressed("Space") {
+            self.walk.boy.air_jump();
+        }
 
         self.walk.boy.update();
+        self.walk.dog.update(&self.walk.boy);
+        self.walk.camera.follow(self.walk.boy.position());
+        self.walk.tutorial = self.walk.tutorial.advance(&self.walk.boy);
 
         let walking_speed = self.walk.velocity();
+        self.walk.parallax.scroll(walking_speed);
 
-            
-        let [first_background, second_background] = &mut self.walk.backgrounds;
-        first_background.move_horizontally(walking_speed);
-        second_background.move_horizontally(walking_speed);
-        
-        if first_background.right() < 0 {
-            first_background.set_x(second_background.right());
-        }
-        if second_background.right() < 0 {
-            second_background.set_x(first_background.right());
-        }
-
-        self.walk.obstacles.retain(|obstacle| obstacle.right() > 0);
+        let left_edge = self.walk.camera.position().x;
+        self.walk
+            .obstacles
+            .retain(|obstacle| obstacle.right() > left_edge && !obstacle.is_finished());
 
         self.walk.obstacles.iter_mut().for_each(|obstacle| {
-            obstacle.move_horizontally(walking_speed);
-            obstacle.check_intersection(&mut self.walk.boy);
+            obstacle.update();
+            obstacle.check_intersection(&mut self.walk.boy, &mut self.walk.effects);
         });
 
-        if self.walk.timeline < TIMELINE_MINIMUM {
-            self.walk.generate_next_segment();
-        } else {
-            self.walk.timeline += walking_speed;
+        self.walk.emit_movement_particles(was_jumping);
+        self.walk.play_sounds();
+        self.walk.hud.update();
+
+        if let Some(shake) = self.walk.effects.take_shake() {
+            if !self.walk.reduce_motion {
+                self.walk.camera.shake(shake.magnitude, shake.decay);
+            }
+        }
+        self.walk.effects.update();
+
+        if self.walk.timeline - left_edge < TIMELINE_MINIMUM {
+            if let Err(err) = self.walk.generate_next_segment() {
+                error!("Could not generate the next segment {:#?}", err);
+            }
         }
-        
+
+        self.walk.rebase();
+
         if self.walk.knocked_out() {
             WalkingEndState::Complete(self.end_game())
         } else {
@@ -194,27 +980,89 @@ impl WalkTheDogState<Walking> {
     }
 }
 
+enum PausedEndState {
+    Resume(WalkTheDogState<Walking>),
+    MainMenu(WalkTheDogState<Title>),
+    Continue(WalkTheDogState<Paused>),
+}
+
+impl WalkTheDogState<Paused> {
+    fn update(self, keystate: &KeyState) -> PausedEndState {
+        let pointer = keystate.pointer();
+        if keystate.just_pressed(BACK_KEY) || pointer.clicked(&RESUME_BUTTON) {
+            PausedEndState::Resume(self.resume())
+        } else if pointer.clicked(&QUIT_BUTTON) {
+            PausedEndState::MainMenu(WalkTheDogState::<Title>::new(Walk::reset(self.walk)))
+        } else {
+            PausedEndState::Continue(self)
+        }
+    }
+
+    fn resume(mut self) -> WalkTheDogState<Walking> {
+        self.walk.music.play(&self.walk.assets, self.walk.walking_music);
+        WalkTheDogState {
+            _state: Walking,
+            walk: self.walk,
+        }
+    }
+}
+
+impl Paused {
+    fn draw(&self, renderer: &Renderer, button: &ImageButton) {
+        renderer.fill_overlay(
+            &Rect::new_from_x_y(0, 0, WIDTH, HEIGHT),
+            PAUSE_OVERLAY_COLOUR,
+            PAUSE_OVERLAY_ALPHA,
+        );
+
+        let ui = Ui::new(renderer);
+        ui.panel(&PAUSE_PANEL, PAUSE_PANEL_COLOUR);
+        ui.text(
+            "Paused",
+            &Point {
+                x: WIDTH / 2,
+                y: PAUSE_PANEL.y() + 50,
+            },
+            &PAUSE_TITLE,
+        );
+        ui.button(button, &RESUME_BUTTON, "Resume", &BUTTON_TEXT);
+        ui.button(button, &QUIT_BUTTON, "Main Menu", &BUTTON_TEXT);
+    }
+}
+
+#[allow(clippy::large_enum_variant)]
 enum GameOverEndState {
     Complete(WalkTheDogState<Ready>),
+    MainMenu(WalkTheDogState<Title>),
     Continue(WalkTheDogState<GameOver>)
 }
 
 impl WalkTheDogState<GameOver> {
     fn update(mut self) -> GameOverEndState {
-        if self._state.new_game_pressed() {
-            GameOverEndState::Complete(self.new_game())
-        } else {
-            GameOverEndState::Continue(self)
+        self.walk.dog.update(&self.walk.boy);
+        self.walk.camera.follow(self.walk.boy.position());
+        self.walk.effects.update();
+        self.walk.hud.update();
+
+        match self._state.update() {
+            Some(GameOverAction::NewGame) => GameOverEndState::Complete(self.new_game()),
+            Some(GameOverAction::MainMenu) => GameOverEndState::MainMenu(self.main_menu()),
+            None => GameOverEndState::Continue(self),
         }
     }
 
-    fn new_game(self) -> WalkTheDogState<Ready> {
-        browser::hide_ui();
+    fn new_game(mut self) -> WalkTheDogState<Ready> {
+        self._state.close_menu();
         WalkTheDogState {
             _state: Ready,
             walk: Walk::reset(self.walk,)
         }
     }
+
+    fn main_menu(mut self) -> WalkTheDogState<Title> {
+        self._state.close_menu();
+        WalkTheDogState::<Title>::new(Walk::reset(self.walk))
+    }
 }
 
 impl<T> WalkTheDogState<T> {
@@ -223,6 +1071,24 @@ impl<T> WalkTheDogState<T> {
     }
 }
 
+impl From<WalkTheDogState<Title>> for WalkTheDogStateMachine {
+    fn from(state: WalkTheDogState<Title>) -> Self {
+        WalkTheDogStateMachine::Title(state)
+    }
+}
+
+impl From<WalkTheDogState<Settings>> for WalkTheDogStateMachine {
+    fn from(state: WalkTheDogState<Settings>) -> Self {
+        WalkTheDogStateMachine::Settings(state)
+    }
+}
+
+impl From<WalkTheDogState<Info>> for WalkTheDogStateMachine {
+    fn from(state: WalkTheDogState<Info>) -> Self {
+        WalkTheDogStateMachine::Info(state)
+    }
+}
+
 impl From<WalkTheDogState<Ready>> for WalkTheDogStateMachine {
     fn from(state: WalkTheDogState<Ready>) -> Self {
         WalkTheDogStateMachine::Ready(state)
@@ -235,77 +1101,411 @@ impl From<WalkTheDogState<Walking>> for WalkTheDogStateMachine {
     }
 }
 
+impl From<WalkTheDogState<Paused>> for WalkTheDogStateMachine {
+    fn from(state: WalkTheDogState<Paused>) -> Self {
+        WalkTheDogStateMachine::Paused(state)
+    }
+}
+
 impl From<WalkTheDogState<GameOver>> for WalkTheDogStateMachine {
     fn from(state: WalkTheDogState<GameOver>) -> Self {
         WalkTheDogStateMachine::GameOver(state)
     }
 }
 
-impl From<ReadyEndState> for WalkTheDogStateMachine {
-    fn from(state: ReadyEndState) -> Self {
+impl From<Loading> for WalkTheDogStateMachine {
+    fn from(state: Loading) -> Self {
+        WalkTheDogStateMachine::Loading(state)
+    }
+}
+
+impl From<LoadingEndState> for WalkTheDogStateMachine {
+    fn from(state: LoadingEndState) -> Self {
         match state {
-            ReadyEndState::Complete(walking) => walking.into(),
-            ReadyEndState::Continue(ready) => ready.into(),
+            LoadingEndState::Complete(title) => (*title).into(),
+            LoadingEndState::Continue(loading) => loading.into(),
         }
     }
 }
 
-impl From<WalkingEndState> for WalkTheDogStateMachine {
-    fn from(state: WalkingEndState) -> Self {
+impl From<TitleEndState> for WalkTheDogStateMachine {
+    fn from(state: TitleEndState) -> Self {
         match state {
-            WalkingEndState::Complete(game_over) => game_over.into(),
-            WalkingEndState::Continue(walking) => walking.into(),
+            TitleEndState::Play(ready) => ready.into(),
+            TitleEndState::Settings(settings) => settings.into(),
+            TitleEndState::Info(info) => info.into(),
+            TitleEndState::Continue(title) => title.into(),
         }
     }
 }
 
-impl From<GameOverEndState> for WalkTheDogStateMachine {
-    fn from(state: GameOverEndState) -> Self {
+impl<T> From<MenuEndState<T>> for WalkTheDogStateMachine
+where
+    WalkTheDogState<T>: Into<WalkTheDogStateMachine>,
+{
+    fn from(state: MenuEndState<T>) -> Self {
         match state {
-            GameOverEndState::Complete(ready) => ready.into(),
-            GameOverEndState::Continue(game_over) => game_over.into(),
+            MenuEndState::Back(title) => title.into(),
+            MenuEndState::Continue(menu) => menu.into(),
         }
     }
 }
 
+impl From<ReadyEndState> for WalkTheDogStateMachine {
+    fn from(state: ReadyEndState) -> Self {
+        match state {
+            ReadyEndState::Complete(walking) => walking.into(),
+            ReadyEndState::Continue(ready) => ready.into(),
+        }
+    }
+}
+
+impl From<WalkingEndState> for WalkTheDogStateMachine {
+    fn from(state: WalkingEndState) -> Self {
+        match state {
+            WalkingEndState::Complete(game_over) => game_over.into(),
+            WalkingEndState::Pause(paused) => paused.into(),
+            WalkingEndState::Continue(walking) => walking.into(),
+        }
+    }
+}
+
+impl From<PausedEndState> for WalkTheDogStateMachine {
+    fn from(state: PausedEndState) -> Self {
+        match state {
+            PausedEndState::Resume(walking) => walking.into(),
+            PausedEndState::MainMenu(title) => title.into(),
+            PausedEndState::Continue(paused) => paused.into(),
+        }
+    }
+}
+
+impl From<GameOverEndState> for WalkTheDogStateMachine {
+    fn from(state: GameOverEndState) -> Self {
+        match state {
+            GameOverEndState::Complete(ready) => ready.into(),
+            GameOverEndState::MainMenu(title) => title.into(),
+            GameOverEndState::Continue(game_over) => game_over.into(),
+        }
+    }
+}
+
+impl Walk {
+    fn new(assets: Rc<Assets>, audio: Audio) -> Result<Self> {
+        let rhb = RedHatBoy::new(assets.get(assets.handle("rhb.json")?)?);
+        let dog = Dog::new(assets.get(assets.handle("dog.json")?)?);
+        let particle_sheet = assets.get(assets.handle("particles.json")?)?;
+        let background = assets.get(assets.handle::<HtmlImageElement>("BG.png")?)?;
+        let clouds = assets.get(assets.handle::<HtmlImageElement>("clouds.svg")?)?;
+        let button = ImageButton::new(
+            (*assets.get(assets.handle::<HtmlImageElement>(BUTTON_IMAGE)?)?).clone(),
+            BUTTON_NORMAL,
+            BUTTON_HOVER,
+            BUTTON_PRESSED,
+        );
+
+        let mut walk = Walk {
+            boy: rhb,
+            dog,
+            camera: new_camera(),
+            effects: Effects::new(
+                Particles::new(PARTICLE_CAPACITY).with_sheet(particle_sheet),
+            ),
+            parallax: Parallax::new(
+                vec![
+                    ParallaxLayer::new(
+                        (*background).clone(),
+                        BACKGROUND_SCROLL,
+                        0,
+                        Tiling::Repeat,
+                    ),
+                    ParallaxLayer::new(
+                        (*clouds).clone(),
+                        CLOUD_SCROLL,
+                        CLOUD_HEIGHT,
+                        Tiling::Single,
+                    ),
+                    ParallaxLayer::new(
+                        (*clouds).clone(),
+                        FOREGROUND_CLOUD_SCROLL,
+                        FOREGROUND_CLOUD_HEIGHT,
+                        Tiling::Single,
+                    )
+                    .in_foreground(),
+                ],
+                WIDTH,
+            ),
+            hud: hud_pop_in(),
+            obstacles: vec![],
+            obstacle_sheet: assets.handle("tiles.json")?,
+            enemy_sheet: assets.handle("enemies.json")?,
+            stone: assets.handle("Stone.png")?,
+            sound_events: sound_events(&assets)?,
+            walking_music: assets.handle(WALKING_MUSIC)?,
+            menu_music: assets.handle(MENU_MUSIC)?,
+            music: MusicController::new(audio.clone()),
+            assets,
+            audio,
+            timeline: 0,
+            reduce_motion: load_reduce_motion(),
+            button,
+            tutorial: Tutorial::Run,
+        };
+        walk.start_obstacles()?;
+
+        Ok(walk)
+    }
+}
+
+impl Tutorial {
+    fn advance(self, boy: &RedHatBoy) -> Self {
+        match self {
+            Tutorial::Run if boy.moving() => Tutorial::Jump,
+            Tutorial::Jump if boy.jumping() => Tutorial::Slide,
+            Tutorial::Slide if boy.sliding() => Tutorial::Done,
+            tutorial => tutorial,
+        }
+    }
+
+    fn prompt(self) -> Option<&'static str> {
+        match self {
+            Tutorial::Run => Some("Press right arrow to run"),
+            Tutorial::Jump => Some("Press space to jump"),
+            Tutorial::Slide => Some("Press down arrow to slide"),
+            Tutorial::Done => None,
+        }
+    }
+}
+
+impl MusicController {
+    fn new(audio: Audio) -> Self {
+        MusicController {
+            audio,
+            plan: MusicPlan::default(),
+            music: None,
+        }
+    }
+
+    fn play(&mut self, assets: &Assets, track: Handle<Sound>) {
+        let change = self.plan.play(track);
+        self.apply(assets, change);
+    }
+
+    fn duck(&mut self, assets: &Assets) {
+        if let Some(change) = self.plan.duck() {
+            self.apply(assets, change);
+        }
+    }
+
+    fn pause(&mut self, assets: &Assets) {
+        if let Some(change) = self.plan.pause() {
+            self.apply(assets, change);
+        }
+    }
+
+    fn apply(&mut self, assets: &Assets, change: MusicChange) {
+        if let Err(err) = self.try_apply(assets, change) {
+            error!("Could not change music {:#?}", err);
+        }
+    }
+
+    fn try_apply(&mut self, assets: &Assets, change: MusicChange) -> Result<()> {
+        match change {
+            MusicChange::Crossfade(track) => {
+                if let Some(music) = self.music.take() {
+                    if let Err(err) = music.stop(MUSIC_CROSSFADE_FRAMES) {
+                        error!("Could not stop music {:#?}", err);
+                    }
+                }
+                let sound = assets.get(track)?;
+                self.music = Some(self.audio.play_music(&sound, MUSIC_VOLUME, MUSIC_CROSSFADE_FRAMES)?);
+            }
+            MusicChange::Resume => {
+                if let Some(music) = &mut self.music {
+                    music.resume()?;
+                    music.fade_to(MUSIC_VOLUME, MUSIC_FADE_FRAMES)?;
+                }
+            }
+            MusicChange::FadeTo(volume) => {
+                if let Some(music) = &mut self.music {
+                    music.fade_to(volume, MUSIC_FADE_FRAMES)?;
+                }
+            }
+            MusicChange::Pause => {
+                if let Some(music) = &mut self.music {
+                    music.pause()?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl MusicPlan {
+    fn play(&mut self, track: Handle<Sound>) -> MusicChange {
+        let paused = self.paused;
+        self.paused = false;
+        if self.track != Some(track) {
+            self.track = Some(track);
+            MusicChange::Crossfade(track)
+        } else if paused {
+            MusicChange::Resume
+        } else {
+            MusicChange::FadeTo(MUSIC_VOLUME)
+        }
+    }
+
+    fn duck(&self) -> Option<MusicChange> {
+        self.track.map(|_| MusicChange::FadeTo(DUCKED_MUSIC_VOLUME))
+    }
+
+    fn pause(&mut self) -> Option<MusicChange> {
+        if self.track.is_none() || self.paused {
+            return None;
+        }
+        self.paused = true;
+        Some(MusicChange::Pause)
+    }
+}
+
 impl WalkTheDog {
     pub fn new() -> Self {
-        WalkTheDog { machine: None }
+        WalkTheDog {
+            machine: None,
+            mute_held: false,
+        }
     }
 }
 
 impl Walk {
     fn reset(walk: Self) -> Self {
-        let starting_obstacles = stone_and_platform(
-            walk.stone.clone(),
-            walk.obstacle_sheet.clone(),
-            0,
-        );
-        let timeline = rightmost(&starting_obstacles);
-
-        Walk {
+        let mut walk = Walk {
             boy: RedHatBoy::reset(walk.boy),
-            backgrounds: walk.backgrounds,
-            obstacles: starting_obstacles,
+            dog: Dog::reset(walk.dog),
+            parallax: walk.parallax,
+            camera: new_camera(),
+            effects: Effects::reset(walk.effects),
+            hud: hud_pop_in(),
+            obstacles: vec![],
+            assets: walk.assets,
+            audio: walk.audio,
+            music: walk.music,
+            sound_events: walk.sound_events,
+            walking_music: walk.walking_music,
+            menu_music: walk.menu_music,
             obstacle_sheet: walk.obstacle_sheet,
+            enemy_sheet: walk.enemy_sheet,
             stone: walk.stone,
-            timeline,
+            timeline: 0,
+            reduce_motion: walk.reduce_motion,
+            button: walk.button,
+            tutorial: walk.tutorial,
+        };
+        if let Err(err) = walk.start_obstacles() {
+            error!("Could not place the first obstacles {:#?}", err);
+        }
+        walk
+    }
+
+    fn idle(&mut self) {
+        self.boy.update();
+        self.dog.update(&self.boy);
+        self.camera.follow(self.boy.position());
+    }
+
+    fn toggle_reduce_motion(&mut self) {
+        self.reduce_motion = !self.reduce_motion;
+        if let Err(err) =
+            browser::save_setting(REDUCE_MOTION_SETTING, &self.reduce_motion.to_string())
+        {
+            error!("Could not save motion setting {:#?}", err);
         }
     }
-    
-    fn generate_next_segment(&mut self) {
+
+    fn start_obstacles(&mut self) -> Result<()> {
+        self.obstacles = stone_and_platform(self.stone()?, self.assets.get(self.obstacle_sheet)?, 0);
+        self.timeline = rightmost(&self.obstacles);
+        Ok(())
+    }
+
+    fn stone(&self) -> Result<HtmlImageElement> {
+        Ok((*self.assets.get(self.stone)?).clone())
+    }
+
+    fn play_sounds(&mut self) {
+        self.sound_events.update();
+        self.audio.set_listener(self.boy.center());
+
+        let boy_sounds = self.boy.take_sounds().into_iter().map(|cue| (cue, None));
+        let world_sounds = self
+            .effects
+            .take_sounds()
+            .into_iter()
+            .map(|(cue, position)| (cue, Some(position)));
+        let mut backend = WebAudio {
+            audio: &self.audio,
+            assets: &self.assets,
+        };
+        for (cue, position) in boy_sounds.chain(world_sounds).collect::<Vec<_>>() {
+            if let Err(err) = self.sound_events.play(cue, position, &mut backend) {
+                log!("Error playing sound {:#?}", err);
+            }
+        }
+    }
+
+    fn generate_next_segment(&mut self) -> Result<()> {
         let mut rng = thread_rng();
-        let next_segment = rng.gen_range(0..2);
+        let next_segment = rng.gen_range(0..10);
+        let stone = self.stone()?;
+        let obstacle_sheet = self.assets.get(self.obstacle_sheet)?;
+        let enemy_sheet = self.assets.get(self.enemy_sheet)?;
 
         let mut next_obstacles = match next_segment {
             0 => stone_and_platform(
-                self.stone.clone(),
-                self.obstacle_sheet.clone(),
+                stone.clone(),
+                obstacle_sheet.clone(),
                 self.timeline + OBSTACLE_BUFFER,
             ),
             1 => platform_and_stone(
-                self.stone.clone(),
-                self.obstacle_sheet.clone(),
+                stone.clone(),
+                obstacle_sheet.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+            ),
+            2 => stone_with_collectibles(
+                stone.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+            ),
+            3 => platform_with_power_up(
+                obstacle_sheet.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+                *POWER_UPS.choose(&mut rng).unwrap_or(&PowerUp::Shield),
+            ),
+            4 => moving_platform_and_stone(
+                stone.clone(),
+                obstacle_sheet.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+            ),
+            5 => crumbling_platform_and_stone(
+                stone.clone(),
+                obstacle_sheet.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+            ),
+            6 => one_way_platform(
+                obstacle_sheet.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+            ),
+            7 => walker_on_platform(
+                obstacle_sheet.clone(),
+                enemy_sheet.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+            ),
+            8 => hopper_and_stone(
+                stone.clone(),
+                enemy_sheet.clone(),
+                self.timeline + OBSTACLE_BUFFER,
+            ),
+            9 => flyer(
+                enemy_sheet.clone(),
                 self.timeline + OBSTACLE_BUFFER,
             ),
             _ => vec![],
@@ -313,16 +1513,129 @@ impl Walk {
 
         self.timeline = rightmost(&next_obstacles);
         self.obstacles.append(&mut next_obstacles);
+        Ok(())
+    }
+
+    fn emit_movement_particles(&mut self, was_jumping: bool) {
+        let feet = self.boy.feet_position();
+
+        if !was_jumping && self.boy.jumping() {
+            self.effects.emit(feet, &JUMP_DUST);
+        } else if was_jumping && self.boy.moving() && !self.boy.jumping() {
+            self.effects.emit(feet, &LANDING_PUFF);
+        }
+
+        if self.boy.sliding() && self.boy.animation_elapsed() % SLIDE_DUST_INTERVAL == 0 {
+            self.effects.emit(feet, &SLIDE_DUST);
+        }
+    }
+
+    fn rebase(&mut self) {
+        if self.boy.position().x > REBASE_DISTANCE {
+            self.boy.shift(-REBASE_DISTANCE);
+            self.dog.shift(-REBASE_DISTANCE);
+            self.camera.shift(-REBASE_DISTANCE);
+            self.effects.shift(-REBASE_DISTANCE);
+            self.obstacles
+                .iter_mut()
+                .for_each(|obstacle| obstacle.move_horizontally(-REBASE_DISTANCE));
+            self.timeline -= REBASE_DISTANCE;
+        }
     }
 
     fn draw(&self, renderer: &Renderer) {
-        self.backgrounds.iter().for_each(|background| {
-            background.draw(renderer);
+        self.parallax.draw(renderer, Depth::Background);
+        renderer.draw_from(&self.camera, |renderer| {
+            self.boy.draw(renderer);
+            self.dog.draw(renderer);
+            self.obstacles.iter().for_each(|obstacle| {
+                obstacle.draw(renderer);
+            });
+            self.effects.draw_particles(renderer);
         });
-        self.boy.draw(renderer);
-        self.obstacles.iter().for_each(|obstacle| {
-            obstacle.draw(renderer);
-        })
+        self.parallax.draw(renderer, Depth::Foreground);
+        if !self.reduce_motion {
+            self.effects
+                .draw(renderer, &Rect::new_from_x_y(0, 0, WIDTH, HEIGHT));
+        }
+        self.draw_hud(renderer);
+    }
+
+    fn draw_hud(&self, renderer: &Renderer) {
+        const HUD_X: i16 = 10;
+        const HUD_Y: i16 = 10;
+        const HUD_WIDTH: i16 = 200;
+        const HUD_PADDING: i16 = 10;
+        const TEXT_HEIGHT: i16 = 22;
+        const TIMER_HEIGHT: i16 = 12;
+        const TIMER_SPACING: i16 = 20;
+        let ui = Ui::new(renderer);
+        let hud_y = HUD_Y + self.hud.value().round() as i16;
+
+        let modifiers = self.boy.modifiers();
+        let active: Vec<PowerUp> = POWER_UPS
+            .iter()
+            .copied()
+            .filter(|power_up| modifiers.is_active(*power_up))
+            .collect();
+
+        ui.panel(
+            &Rect::new_from_x_y(
+                HUD_X,
+                hud_y,
+                HUD_WIDTH,
+                TEXT_HEIGHT + TIMER_SPACING * active.len() as i16 + HUD_PADDING,
+            ),
+            HUD_PANEL_COLOUR,
+        );
+        let text_y = hud_y + HUD_PADDING + TEXT_HEIGHT / 2 - 4;
+        ui.text(
+            &format!("Lives {}", self.boy.lives()),
+            &Point {
+                x: HUD_X + HUD_PADDING,
+                y: text_y,
+            },
+            &HUD_TEXT,
+        );
+        ui.text(
+            &format!("Collected {}", self.boy.collected()),
+            &Point {
+                x: HUD_X + HUD_WIDTH - HUD_PADDING,
+                y: text_y,
+            },
+            &TextStyle {
+                align: Align::Right,
+                ..HUD_TEXT
+            },
+        );
+        active.iter().enumerate().for_each(|(index, power_up)| {
+            ui.progress_bar(
+                &Rect::new_from_x_y(
+                    HUD_X + HUD_PADDING,
+                    hud_y + TEXT_HEIGHT + TIMER_SPACING * index as i16 + HUD_PADDING / 2,
+                    HUD_WIDTH - HUD_PADDING * 2,
+                    TIMER_HEIGHT,
+                ),
+                modifiers.progress(*power_up),
+                HUD_BAR_BACKGROUND,
+                power_up_colour(*power_up),
+            );
+        });
+    }
+
+    fn draw_tutorial(&self, renderer: &Renderer) {
+        if let Some(prompt) = self.tutorial.prompt() {
+            let ui = Ui::new(renderer);
+            ui.panel(&PROMPT_PANEL, HUD_PANEL_COLOUR);
+            ui.text(
+                prompt,
+                &Point {
+                    x: PROMPT_PANEL.x() + PROMPT_PANEL.width / 2,
+                    y: PROMPT_PANEL.y() + PROMPT_PANEL.height / 2,
+                },
+                &PROMPT_TEXT,
+            );
+        }
     }
 
     fn knocked_out(&self) -> bool {
@@ -335,44 +1648,31 @@ impl Walk {
 }
 
 impl RedHatBoy {
-    fn new(sheet: Sheet, image: HtmlImageElement, audio: Audio, jump_sound: Sound) -> Self {
+    fn new(sheet: Rc<SpriteSheet>) -> Self {
+        let animations = red_hat_boy_animations(sheet.sheet());
+        RedHatBoy::with_animations(sheet, animations)
+    }
+
+    fn with_animations(sheet: Rc<SpriteSheet>, animations: Animations) -> Self {
         RedHatBoy {
-            state_machine: RedHatBoyStateMachine::Idle(RedHatBoyState::new(audio, jump_sound)),
+            state_machine: RedHatBoyStateMachine::Idle(RedHatBoyState::new(animations.clone())),
+            animations,
             sprite_sheet: sheet,
-            image,
         }
     }
 
     fn reset(boy: Self) -> Self {
-        RedHatBoy::new(
-            boy.sprite_sheet,
-            boy.image,
-            boy.state_machine.context().audio.clone(),
-            boy.state_machine.context().jump_sound.clone(),
-        )
-    }
-
-    fn frame_name(&self) -> String {
-        format!(
-            "{} ({}).png",
-            self.state_machine.frame_name(),
-            (self.state_machine.context().frame / 3) + 1,
-        )
+        RedHatBoy::with_animations(boy.sprite_sheet, boy.animations)
     }
 
     fn current_sprite(&self) -> Option<&Cell> {
-        self.sprite_sheet.frames.get(&self.frame_name())
+        self.sprite_sheet.cell(self.state_machine.frame_name())
     }
 
     fn destination_box(&self) -> Rect {
-        let sprite = self.current_sprite().expect("Cell not found");
-
-        Rect::new_from_x_y(
-            self.state_machine.context().position.x + sprite.sprite_source_size.x,
-            self.state_machine.context().position.y + sprite.sprite_source_size.y,
-            sprite.frame.w,
-            sprite.frame.h,
-        )
+        self.current_sprite()
+            .expect("Cell not found")
+            .destination(self.state_machine.context().position)
     }
 
     fn bounding_box(&self) -> Rect {
@@ -389,6 +1689,14 @@ impl RedHatBoy {
         )
     }
 
+    fn position(&self) -> Point {
+        self.state_machine.context().position
+    }
+
+    fn shift(&mut self, x: i16) {
+        self.state_machine = self.state_machine.clone().transition(Event::Shift(x));
+    }
+
     fn pos_y(&self) -> i16 {
         self.state_machine.context().position.y
     }
@@ -402,18 +1710,18 @@ impl RedHatBoy {
     }
 
     fn draw(&self, renderer: &Renderer) {
+        if self.modifiers().is_flickering() {
+            return;
+        }
+
         let sprite = self.current_sprite().expect("Cell not found");
 
-        renderer.draw_image(
-            &self.image,
-            &Rect::new_from_x_y(
-                sprite.frame.x,
-                sprite.frame.y,
-                sprite.frame.w,
-                sprite.frame.h,
-            ),
-            &self.destination_box(),
-        );
+        self.sprite_sheet
+            .draw_cell(renderer, sprite, &self.destination_box());
+
+        if self.modifiers().is_active(PowerUp::Shield) {
+            renderer.stroke_circle(&self.center(), SHIELD_RADIUS, power_up_colour(PowerUp::Shield));
+        }
 
         // renderer.draw_rect(&self.bounding_box());
     }
@@ -434,6 +1742,10 @@ impl RedHatBoy {
         self.state_machine = self.state_machine.clone().transition(Event::Jump);
     }
 
+    fn air_jump(&mut self) {
+        self.state_machine = self.state_machine.clone().transition(Event::AirJump);
+    }
+
     fn knock_out(&mut self) {
         self.state_machine = self.state_machine.clone().transition(Event::KnockOut);
     }
@@ -442,31 +1754,159 @@ impl RedHatBoy {
         self.state_machine = self.state_machine.clone().transition(Event::Land(position));
     }
 
+    fn bounce(&mut self) {
+        self.state_machine = self.state_machine.clone().transition(Event::Bounce);
+    }
+
+    fn power_up(&mut self, power_up: PowerUp) {
+        self.state_machine = self.state_machine.clone().transition(Event::PowerUp(power_up));
+    }
+
+    fn collect(&mut self) {
+        self.state_machine = self.state_machine.clone().transition(Event::Collect);
+    }
+
+    fn modifiers(&self) -> &Modifiers {
+        &self.state_machine.context().modifiers
+    }
+
+    fn can_collect(&self) -> bool {
+        self.state_machine.can_collect()
+    }
+
+    fn collected(&self) -> u16 {
+        self.state_machine.context().collected
+    }
+
+    fn lives(&self) -> u8 {
+        self.state_machine.context().lives
+    }
+
+    fn center(&self) -> Point {
+        let bounding_box = self.bounding_box();
+
+        Point {
+            x: bounding_box.x() + bounding_box.width / 2,
+            y: bounding_box.y() + bounding_box.height / 2,
+        }
+    }
+
     fn knocked_out(&self) -> bool {
         self.state_machine.knocked_out()
     }
+
+    fn jumping(&self) -> bool {
+        self.state_machine.jumping()
+    }
+
+    fn sliding(&self) -> bool {
+        self.state_machine.sliding()
+    }
+
+    fn take_sounds(&mut self) -> Vec<SoundCue> {
+        self.state_machine.take_sounds()
+    }
+
+    fn animation_elapsed(&self) -> u16 {
+        self.state_machine.context().animation.elapsed()
+    }
+
+    fn feet_position(&self) -> Point {
+        Point {
+            x: self.center().x,
+            y: self.feet(),
+        }
+    }
+
+    fn moving(&self) -> bool {
+        self.state_machine.moving()
+    }
+
+    fn feet(&self) -> i16 {
+        self.state_machine.context().feet()
+    }
+}
+
+impl Dog {
+    fn new(sprite_sheet: Rc<SpriteSheet>) -> Self {
+        let animations = dog_animations(sprite_sheet.sheet());
+        Dog::with_animations(sprite_sheet, animations)
+    }
+
+    fn with_animations(sprite_sheet: Rc<SpriteSheet>, animations: Animations) -> Self {
+        Dog {
+            state_machine: DogStateMachine::Waiting(DogState::new(animations.clone())),
+            animations,
+            sprite_sheet,
+            trail: VecDeque::with_capacity(FOLLOW_DELAY + 1),
+        }
+    }
+
+    fn reset(dog: Self) -> Self {
+        Dog::with_animations(dog.sprite_sheet, dog.animations)
+    }
+
+    fn update(&mut self, boy: &RedHatBoy) {
+        self.trail.push_back(Footstep {
+            x: boy.position().x,
+            ground: boy.feet(),
+            moving: boy.moving(),
+            jumping: boy.jumping(),
+        });
+
+        if self.trail.len() > FOLLOW_DELAY {
+            if let Some(footstep) = self.trail.pop_front() {
+                self.follow(footstep);
+            }
+        }
+
+        self.state_machine = self.state_machine.clone().update();
+    }
+
+    fn follow(&mut self, footstep: Footstep) {
+        let mut machine = self.state_machine.clone();
+        if !footstep.moving {
+            machine = machine.transition(DogEvent::Wait);
+        } else if footstep.jumping {
+            machine = machine.transition(DogEvent::Follow).transition(DogEvent::Jump);
+        } else {
+            machine = machine.transition(DogEvent::Follow).transition(DogEvent::Land);
+        }
+        self.state_machine = machine.transition(DogEvent::Track(Point {
+            x: footstep.x,
+            y: footstep.ground,
+        }));
+    }
+
+    fn shift(&mut self, x: i16) {
+        self.trail.iter_mut().for_each(|footstep| footstep.x += x);
+        self.state_machine = self.state_machine.clone().transition(DogEvent::Shift(x));
+    }
+
+    fn draw(&self, renderer: &Renderer) {
+        if let Some(sprite) = self.sprite_sheet.cell(self.state_machine.frame_name()) {
+            self.sprite_sheet.draw_cell(
+                renderer,
+                sprite,
+                &sprite.destination(self.state_machine.context().position),
+            );
+        }
+    }
 }
 
 impl Obstacle for Platform {
     fn draw(&self, renderer: &Renderer) {
-        let mut x = 0;
+        let mut x = self.shake();
         self.sprites.iter().for_each(|sprite| {
-            self.sheet.draw(
+            self.sheet.draw_cell(
                 renderer,
-                &Rect::new_from_x_y(
-                    sprite.frame.x,
-                    sprite.frame.y,
-                    sprite.frame.w,
-                    sprite.frame.h,
-                ),
-                &Rect::new_from_x_y(
-                    self.position.x + x,
-                    self.position.y,
-                    sprite.frame.w,
-                    sprite.frame.h,
-                ),
+                sprite,
+                &sprite.destination(Point {
+                    x: self.position.x + x,
+                    y: self.position.y,
+                }),
             );
-            x += sprite.frame.w;
+            x += sprite.source_size().w;
         });
 
         // for bounding_box in self.bounding_boxes() {
@@ -481,20 +1921,53 @@ impl Obstacle for Platform {
         })
     }
 
-    fn check_intersection(&self, boy: &mut RedHatBoy) {
-        if let Some(box_to_land_on) = self
+    fn check_intersection(&mut self, boy: &mut RedHatBoy, effects: &mut Effects) {
+        if let Some(landing_y) = self
             .bounding_boxes()
             .iter()
             .find(|&bounding_box| boy.bounding_box().intersects(bounding_box))
+            .map(|bounding_box| bounding_box.y())
         {
-            if boy.velocity_y() > 0 && boy.pos_y() < self.position.y {
-                boy.land_on(box_to_land_on.y());
-            } else {
+            if self.can_land(boy, landing_y) {
+                boy.land_on(landing_y);
+                self.start_crumbling();
+            } else if !self.one_way {
+                impact(boy, effects);
                 boy.knock_out();
             }
         }
     }
 
+    fn update(&mut self) {
+        match &mut self.motion {
+            Motion::Static => {}
+            Motion::Oscillating(tween) => {
+                tween.update();
+                let target = tween.value().round() as i16;
+                self.move_vertically(target - self.position.y);
+            }
+            Motion::Crumbling {
+                countdown: Some(0),
+                fall_speed,
+            } => {
+                *fall_speed = (*fall_speed + PLATFORM_GRAVITY).min(PLATFORM_TERMINAL_VELOCITY);
+                let fall_speed = *fall_speed;
+                self.move_vertically(fall_speed);
+            }
+            Motion::Crumbling {
+                countdown: Some(countdown),
+                ..
+            } => {
+                *countdown -= 1;
+            }
+            Motion::Crumbling { countdown: None, .. } => {}
+        }
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position.y > HEIGHT
+    }
+
     fn right(&self) -> i16 {
         self.bounding_boxes()
             .last()
@@ -512,8 +1985,9 @@ impl Obstacle for Barrier {
         self.image.move_horizontally(x);
     }
 
-    fn check_intersection(&self, boy: &mut RedHatBoy) {
+    fn check_intersection(&mut self, boy: &mut RedHatBoy, effects: &mut Effects) {
         if boy.bounding_box().intersects(self.image.bounding_box()) {
+            impact(boy, effects);
             boy.knock_out();
         }
     }
@@ -523,6 +1997,186 @@ impl Obstacle for Barrier {
     }
 }
 
+impl Obstacle for Enemy {
+    fn draw(&self, renderer: &Renderer) {
+        if let Some(sprite) = self.current_sprite() {
+            self.sheet
+                .draw_cell(renderer, sprite, &sprite.destination(self.position));
+        }
+    }
+
+    fn move_horizontally(&mut self, x: i16) {
+        self.position.x += x;
+    }
+
+    fn check_intersection(&mut self, boy: &mut RedHatBoy, effects: &mut Effects) {
+        if self.defeated.is_some() {
+            return;
+        }
+
+        if !self.announced && self.position.x - boy.position().x < ENEMY_NEAR_DISTANCE {
+            self.announced = true;
+            effects.play_sound(SoundCue::EnemyNear, self.position);
+        }
+
+        let bounding_box = self.bounding_box();
+        if boy.bounding_box().intersects(&bounding_box) {
+            if self.can_be_stomped() && boy.velocity_y() > 0 && boy.feet() - boy.velocity_y() <= bounding_box.y() {
+                self.defeated = Some(ENEMY_DEFEAT_BOUNCE);
+                effects.shake(STOMP_SHAKE, SHAKE_DECAY);
+                effects.hit_stop(STOMP_HIT_STOP);
+                effects.play_sound(SoundCue::Stomp, bounding_box.position);
+                boy.bounce();
+            } else {
+                impact(boy, effects);
+                boy.knock_out();
+            }
+        }
+    }
+
+    fn update(&mut self) {
+        self.animation.update();
+
+        if let Some(fall_speed) = self.defeated {
+            self.position.y += fall_speed;
+            self.defeated = Some((fall_speed + ENEMY_GRAVITY).min(ENEMY_TERMINAL_VELOCITY));
+            return;
+        }
+
+        self.behaviour = match self.behaviour {
+            EnemyBehaviour::Walker {
+                patrol,
+                speed,
+                travelled,
+            } => {
+                let speed = if (travelled + speed) < 0 || (travelled + speed) > patrol {
+                    -speed
+                } else {
+                    speed
+                };
+                self.position.x += speed;
+                EnemyBehaviour::Walker {
+                    patrol,
+                    speed,
+                    travelled: travelled + speed,
+                }
+            }
+            EnemyBehaviour::Hopper {
+                ground,
+                velocity,
+                rest,
+            } => {
+                if rest > 0 {
+                    EnemyBehaviour::Hopper {
+                        ground,
+                        velocity,
+                        rest: rest - 1,
+                    }
+                } else {
+                    self.position.y = (self.position.y + velocity).min(ground);
+                    if self.position.y >= ground && velocity > 0 {
+                        EnemyBehaviour::Hopper {
+                            ground,
+                            velocity: HOPPER_JUMP_SPEED,
+                            rest: HOPPER_REST,
+                        }
+                    } else {
+                        EnemyBehaviour::Hopper {
+                            ground,
+                            velocity: (velocity + ENEMY_GRAVITY).min(ENEMY_TERMINAL_VELOCITY),
+                            rest,
+                        }
+                    }
+                }
+            }
+            EnemyBehaviour::Flyer {
+                origin,
+                amplitude,
+                period,
+                tick,
+            } => {
+                let tick = (tick + 1) % period;
+                let angle = std::f32::consts::PI * 2.0 * tick as f32 / period as f32;
+                self.position.x += FLYER_DRIFT;
+                self.position.y = origin + (amplitude as f32 * angle.sin()) as i16;
+                EnemyBehaviour::Flyer {
+                    origin,
+                    amplitude,
+                    period,
+                    tick,
+                }
+            }
+        };
+    }
+
+    fn right(&self) -> i16 {
+        self.bounding_box().right()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.position.y > HEIGHT
+    }
+}
+
+impl Obstacle for Collectible {
+    fn draw(&self, renderer: &Renderer) {
+        renderer.fill_circle(&self.position, COLLECTIBLE_RADIUS, COLLECTIBLE_COLOUR);
+    }
+
+    fn move_horizontally(&mut self, x: i16) {
+        self.position.x += x;
+    }
+
+    fn check_intersection(&mut self, boy: &mut RedHatBoy, effects: &mut Effects) {
+        if boy.modifiers().is_active(PowerUp::Magnet) {
+            self.position = pull_towards(self.position, boy.center());
+        }
+
+        if boy.can_collect() && boy.bounding_box().intersects(&self.bounding_box()) {
+            self.collected = true;
+            effects.emit(self.position, &SPARKLE);
+            effects.play_sound(SoundCue::Collect, self.position);
+            boy.collect();
+        }
+    }
+
+    fn right(&self) -> i16 {
+        self.bounding_box().right()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.collected
+    }
+}
+
+impl Obstacle for PowerUpPickup {
+    fn draw(&self, renderer: &Renderer) {
+        renderer.fill_circle(&self.position, POWER_UP_RADIUS, power_up_colour(self.power_up));
+        renderer.stroke_circle(&self.position, POWER_UP_RADIUS, COLLECTIBLE_COLOUR);
+    }
+
+    fn move_horizontally(&mut self, x: i16) {
+        self.position.x += x;
+    }
+
+    fn check_intersection(&mut self, boy: &mut RedHatBoy, effects: &mut Effects) {
+        if boy.can_collect() && boy.bounding_box().intersects(&self.bounding_box()) {
+            self.collected = true;
+            effects.emit(self.position, &SPARKLE);
+            effects.play_sound(SoundCue::Collect, self.position);
+            boy.power_up(self.power_up);
+        }
+    }
+
+    fn right(&self) -> i16 {
+        self.bounding_box().right()
+    }
+
+    fn is_finished(&self) -> bool {
+        self.collected
+    }
+}
+
 impl Platform {
     pub fn new(
         sheet: Rc<SpriteSheet>,
@@ -552,9 +2206,81 @@ impl Platform {
             position,
             sprites,
             bounding_boxes,
+            motion: Motion::Static,
+            one_way: false,
+        }
+    }
+
+    pub fn oscillating(mut self, amplitude: i16, period: u16) -> Self {
+        let origin = self.position.y as f32;
+        let amplitude = amplitude as f32;
+        let quarter = (period / 4).max(1);
+        self.motion = Motion::Oscillating(
+            Tween::new(origin, origin + amplitude, quarter, Easing::QuadOut)
+                .then(origin - amplitude, quarter * 2, Easing::QuadInOut)
+                .then(origin, quarter, Easing::QuadIn)
+                .repeat(),
+        );
+        self
+    }
+
+    pub fn crumbling(mut self) -> Self {
+        self.motion = Motion::Crumbling {
+            countdown: None,
+            fall_speed: 0,
+        };
+        self
+    }
+
+    pub fn one_way(mut self) -> Self {
+        self.one_way = true;
+        self
+    }
+
+    fn can_land(&self, boy: &RedHatBoy, landing_y: i16) -> bool {
+        if self.one_way {
+            boy.velocity_y() > 0 && boy.feet() - boy.velocity_y() <= landing_y
+        } else {
+            boy.velocity_y() > 0 && boy.pos_y() < self.position.y
+        }
+    }
+
+    fn start_crumbling(&mut self) {
+        if let Motion::Crumbling {
+            countdown: None,
+            fall_speed,
+        } = self.motion
+        {
+            self.motion = Motion::Crumbling {
+                countdown: Some(CRUMBLE_DELAY),
+                fall_speed,
+            };
+        }
+    }
+
+    fn shake(&self) -> i16 {
+        match self.motion {
+            Motion::Crumbling {
+                countdown: Some(countdown),
+                ..
+            } if countdown > 0 => {
+                if countdown % 4 < 2 {
+                    CRUMBLE_SHAKE
+                } else {
+                    -CRUMBLE_SHAKE
+                }
+            }
+            _ => 0,
         }
     }
 
+    fn move_vertically(&mut self, y: i16) {
+        self.position.y += y;
+        self.bounding_boxes.iter_mut().for_each(|bounding_box| {
+            bounding_box.set_y(bounding_box.position.y + y);
+        })
+    }
+
     fn bounding_boxes(&self) -> &Vec<Rect> {
         &self.bounding_boxes
     }
@@ -566,52 +2292,177 @@ impl Barrier {
     }
 }
 
+impl Enemy {
+    pub fn new(sheet: Rc<SpriteSheet>, behaviour: EnemyBehaviour, position: Point) -> Self {
+        let (name, playback) = match behaviour {
+            EnemyBehaviour::Walker { .. } => ("Walker", Playback::Loop),
+            EnemyBehaviour::Hopper { .. } => ("Hopper", Playback::Loop),
+            EnemyBehaviour::Flyer { .. } => ("Flyer", Playback::PingPong),
+        };
+
+        Enemy {
+            animation: Animation::new(sheet.sheet(), name, ENEMY_FRAME_DURATION, playback),
+            sheet,
+            behaviour,
+            position,
+            defeated: None,
+            announced: false,
+        }
+    }
+
+    pub fn walker(sheet: Rc<SpriteSheet>, position: Point, patrol: i16, speed: i16) -> Self {
+        Enemy::new(
+            sheet,
+            EnemyBehaviour::Walker {
+                patrol,
+                speed,
+                travelled: 0,
+            },
+            position,
+        )
+    }
+
+    pub fn hopper(sheet: Rc<SpriteSheet>, position: Point) -> Self {
+        Enemy::new(
+            sheet,
+            EnemyBehaviour::Hopper {
+                ground: position.y,
+                velocity: 0,
+                rest: HOPPER_REST,
+            },
+            position,
+        )
+    }
+
+    pub fn flyer(sheet: Rc<SpriteSheet>, position: Point, amplitude: i16, period: u16) -> Self {
+        Enemy::new(
+            sheet,
+            EnemyBehaviour::Flyer {
+                origin: position.y,
+                amplitude,
+                period: period.max(1),
+                tick: 0,
+            },
+            position,
+        )
+    }
+
+    fn can_be_stomped(&self) -> bool {
+        !matches!(self.behaviour, EnemyBehaviour::Flyer { .. })
+    }
+
+    fn current_sprite(&self) -> Option<&Cell> {
+        self.sheet.cell(self.animation.frame_name())
+    }
+
+    fn bounding_box(&self) -> Rect {
+        match self.current_sprite() {
+            Some(sprite) => sprite.destination(self.position),
+            None => Rect::new(self.position, 0, 0),
+        }
+    }
+}
+
+impl Collectible {
+    pub fn new(position: Point) -> Self {
+        Collectible {
+            position,
+            collected: false,
+        }
+    }
+
+    fn bounding_box(&self) -> Rect {
+        Rect::new_from_x_y(
+            self.position.x - COLLECTIBLE_RADIUS,
+            self.position.y - COLLECTIBLE_RADIUS,
+            COLLECTIBLE_RADIUS * 2,
+            COLLECTIBLE_RADIUS * 2,
+        )
+    }
+}
+
+impl PowerUpPickup {
+    pub fn new(power_up: PowerUp, position: Point) -> Self {
+        PowerUpPickup {
+            power_up,
+            position,
+            collected: false,
+        }
+    }
+
+    fn bounding_box(&self) -> Rect {
+        Rect::new_from_x_y(
+            self.position.x - POWER_UP_RADIUS,
+            self.position.y - POWER_UP_RADIUS,
+            POWER_UP_RADIUS * 2,
+            POWER_UP_RADIUS * 2,
+        )
+    }
+}
+
+fn impact(boy: &RedHatBoy, effects: &mut Effects) {
+    if !boy.moving() {
+        return;
+    }
+
+    if boy.modifiers().is_active(PowerUp::Shield) {
+        effects.shake(SHIELD_SHAKE, SHAKE_DECAY);
+        effects.flash(power_up_colour(PowerUp::Shield), HIT_FLASH_FRAMES);
+    } else if !boy.modifiers().is_protected() {
+        effects.shake(HIT_SHAKE, SHAKE_DECAY);
+        effects.hit_stop(HIT_STOP);
+        effects.flash(HIT_FLASH, HIT_FLASH_FRAMES);
+    }
+}
+
+fn load_reduce_motion() -> bool {
+    match browser::load_setting(REDUCE_MOTION_SETTING) {
+        Ok(setting) => setting.as_deref() == Some("true"),
+        Err(err) => {
+            error!("Could not load motion setting {:#?}", err);
+            false
+        }
+    }
+}
+
+fn new_camera() -> Camera {
+    Camera::new(CAMERA_ANCHOR)
+        .smoothing(CAMERA_SMOOTHING)
+        .vertical_follow(CAMERA_VERTICAL_MARGIN)
+}
+
+fn power_up_colour(power_up: PowerUp) -> &'static str {
+    match power_up {
+        PowerUp::Shield => "#3DA5FF",
+        PowerUp::Magnet => "#E8433B",
+        PowerUp::DoubleJump => "#7ED957",
+    }
+}
+
+fn pull_towards(position: Point, target: Point) -> Point {
+    let dx = target.x - position.x;
+    let dy = target.y - position.y;
+
+    if dx.abs() > MAGNET_RANGE || dy.abs() > MAGNET_RANGE {
+        position
+    } else {
+        Point {
+            x: position.x + dx.signum() * MAGNET_SPEED.min(dx.abs()),
+            y: position.y + dy.signum() * MAGNET_SPEED.min(dy.abs()),
+        }
+    }
+}
+
 #[async_trait(?Send)]
 impl Game for WalkTheDog {
     async fn initialize(&self) -> Result<Box<dyn Game>> {
         match self.machine {
             None => {
-                let json = browser::fetch_json("rhb.json").await?;
                 let audio = Audio::new()?;
-                let sound = audio.load_sound("SFX_Jump_23.mp3").await?;
-                let background_music = audio.load_sound("background_song.mp3").await?;
-                // audio.play_looping_sound(&background_music)?;
-        
-                let rhb = RedHatBoy::new(
-                    json.into_serde::<Sheet>()?,
-                    engine::load_image("rhb.png").await?,
-                    audio,
-                    sound,
-                );
-
-                let background = engine::load_image("BG.png").await?;
-                let stone = engine::load_image("Stone.png").await?;
-
-                let tiles = browser::fetch_json("tiles.json").await?;
-                let sprite_sheet = Rc::new(SpriteSheet::new(
-                    tiles.into_serde::<Sheet>()?,
-                    engine::load_image("tiles.png").await?,
-                ));
-
-                let background_width = background.width() as i16;
-
-                let starting_obstacles = stone_and_platform(stone.clone(), sprite_sheet.clone(), 0);
-                let timeline = rightmost(&starting_obstacles);
-
-                let machine = WalkTheDogStateMachine::new(Walk {
-                    boy: rhb,
-                    backgrounds: [
-                        Image::new(background.clone(), Point { x: 0, y: 0 }),
-                        Image::new(background, Point { x: background_width, y: 0}),
-                    ],
-                    obstacles: starting_obstacles,
-                    obstacle_sheet: sprite_sheet,
-                    stone,
-                    timeline,
-                });
 
                 Ok(Box::new(WalkTheDog {
-                    machine: Some(machine),
+                    machine: Some(WalkTheDogStateMachine::Loading(Loading::start(audio))),
+                    mute_held: false,
                 }))
             },
             Some(_) => Err(anyhow!("Error: Game is already initialized!")),
@@ -619,25 +2470,134 @@ impl Game for WalkTheDog {
     }
 
     fn update(&mut self, keystate: &KeyState) {
+        let mute_pressed = keystate.is_pressed(MUTE_KEY);
+        if mute_pressed && !self.mute_held {
+            if let Some(machine) = &self.machine {
+                machine.audio().toggle_mute();
+            }
+        }
+        self.mute_held = mute_pressed;
+
         if let Some(machine) = self.machine.take() {
             self.machine.replace(machine.update(keystate));  
         }
         assert!(self.machine.is_some());
     }
 
+    fn take_hit_stop(&mut self) -> u8 {
+        self.machine
+            .as_mut()
+            .map_or(0, |machine| machine.take_hit_stop())
+    }
+
+    fn audio(&self) -> Option<Audio> {
+        self.machine.as_ref().map(|machine| machine.audio().clone())
+    }
+
     fn draw(&self, renderer: &Renderer) {
-        renderer.clear(&Rect::new(Point { x: 0, y: 0 }, 600, 600));
+        renderer.clear(&Rect::new(Point { x: 0, y: 0 }, WIDTH, HEIGHT));
 
         if let Some(machine) = &self.machine {
             machine.draw(renderer);
+
+            if machine.audio().state() == AudioState::Suspended {
+                if let Err(err) = renderer.draw_text(SOUND_LOCKED_HINT, &SOUND_LOCKED_POSITION) {
+                    error!("Could not draw sound hint {:#?}", err);
+                }
+            }
         }
     }
 }
 
-fn rightmost(obstacle_list: &Vec<Box<dyn Obstacle>>) -> i16 {
+fn sound_events(assets: &Assets) -> Result<SoundEvents> {
+    Ok(SoundEvents::default()
+        .with(
+            SoundCue::Jump,
+            SoundEvent::new(variations(assets, &[JUMP_SOUND, "jump_2"])?)
+                .rate(0.95, 1.05)
+                .gain(0.45, 0.55)
+                .max_voices(2),
+        )
+        .with(
+            SoundCue::Land,
+            SoundEvent::new(variations(assets, &["land_1", "land_2"])?)
+                .rate(0.9, 1.1)
+                .gain(0.5, 0.7)
+                .cooldown(6),
+        )
+        .with(
+            SoundCue::Slide,
+            SoundEvent::new(variations(assets, &["slide_1", "slide_2"])?)
+                .rate(0.9, 1.1)
+                .cooldown(10)
+                .max_voices(1),
+        )
+        .with(
+            SoundCue::KnockOut,
+            SoundEvent::new(variations(assets, &["knock_out_1", "knock_out_2"])?)
+                .max_voices(1),
+        )
+        .with(
+            SoundCue::Stomp,
+            SoundEvent::new(variations(assets, &["stomp_1", "stomp_2"])?)
+                .rate(0.9, 1.1)
+                .max_voices(2),
+        )
+        .with(
+            SoundCue::Collect,
+            SoundEvent::new(variations(assets, &["collect_1", "collect_2"])?)
+                .rate(0.95, 1.2)
+                .gain(0.8, 1.0)
+                .cooldown(3)
+                .max_voices(3),
+        )
+        .with(
+            SoundCue::EnemyNear,
+            SoundEvent::new(variations(assets, &["enemy_near_1", "enemy_near_2"])?)
+                .rate(0.9, 1.1)
+                .max_voices(2),
+        ))
+}
+
+fn variations(assets: &Assets, paths: &[&str]) -> Result<Vec<Handle<Sound>>> {
+    paths.iter().map(|path| assets.handle(path)).collect()
+}
+
+fn hud_pop_in() -> Tween {
+    Tween::new(HUD_HIDDEN_OFFSET, 0.0, HUD_POP_IN_FRAMES, Easing::BackOut)
+}
+
+fn rightmost(obstacle_list: &[Box<dyn Obstacle>]) -> i16 {
     obstacle_list
         .iter()
         .map(|obstacle| obstacle.right())
-        .max_by(|x, y| x.cmp(&y))
+        .max_by(|x, y| x.cmp(y))
         .unwrap_or(0)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn music_crossfades_between_tracks_and_resumes_after_a_pause() {
+        let mut assets = Assets::new();
+        let menu = assets.register::<Sound>(MENU_MUSIC);
+        let walking = assets.register::<Sound>(WALKING_MUSIC);
+        let mut plan = MusicPlan::default();
+
+        assert_eq!(plan.duck(), None);
+        assert_eq!(plan.pause(), None);
+        assert_eq!(plan.play(menu), MusicChange::Crossfade(menu));
+        assert_eq!(plan.play(menu), MusicChange::FadeTo(MUSIC_VOLUME));
+        assert_eq!(plan.play(walking), MusicChange::Crossfade(walking));
+
+        assert_eq!(plan.pause(), Some(MusicChange::Pause));
+        assert_eq!(plan.pause(), None);
+        assert_eq!(plan.play(walking), MusicChange::Resume);
+
+        assert_eq!(plan.duck(), Some(MusicChange::FadeTo(DUCKED_MUSIC_VOLUME)));
+        assert_eq!(plan.play(walking), MusicChange::FadeTo(MUSIC_VOLUME));
+        assert_eq!(plan.play(menu), MusicChange::Crossfade(menu));
+    }
+}
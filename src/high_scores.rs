@@ -0,0 +1,92 @@
+use anyhow::Result;
+
+use crate::browser;
+
+const HIGH_SCORES: &str = "walk_the_dog.high_scores";
+const MAX_HIGH_SCORES: usize = 5;
+
+#[derive(Debug, Default, PartialEq)]
+pub struct HighScores {
+    scores: Vec<u16>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        match browser::load_setting(HIGH_SCORES) {
+            Ok(Some(setting)) => HighScores::parse(&setting),
+            Ok(None) => HighScores::default(),
+            Err(err) => {
+                error!("Could not load high scores {:#?}", err);
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        browser::save_setting(HIGH_SCORES, &self.to_setting())
+    }
+
+    fn parse(setting: &str) -> Self {
+        let mut high_scores = HighScores {
+            scores: setting
+                .split(',')
+                .filter_map(|score| score.trim().parse().ok())
+                .collect(),
+        };
+        high_scores.sort();
+        high_scores
+    }
+
+    fn to_setting(&self) -> String {
+        self.scores
+            .iter()
+            .map(u16::to_string)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn record(&mut self, score: u16) -> bool {
+        if score == 0 {
+            return false;
+        }
+
+        let qualifies = self.scores.len() < MAX_HIGH_SCORES
+            || self.scores.last().map_or(true, |&lowest| score > lowest);
+        if qualifies {
+            self.scores.push(score);
+            self.sort();
+        }
+        qualifies
+    }
+
+    pub fn scores(&self) -> &[u16] {
+        &self.scores
+    }
+
+    fn sort(&mut self) {
+        self.scores.sort_unstable_by(|a, b| b.cmp(a));
+        self.scores.truncate(MAX_HIGH_SCORES);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_best_five_scores_in_order() {
+        let mut high_scores = HighScores::parse("3, 12,oops,7");
+        assert_eq!(high_scores.scores(), &[12, 7, 3]);
+
+        assert!(high_scores.record(5));
+        assert!(high_scores.record(20));
+        assert!(!high_scores.record(1));
+        assert!(!high_scores.record(0));
+        assert_eq!(high_scores.scores(), &[20, 12, 7, 5, 3]);
+        assert!(!high_scores.record(3));
+        assert_eq!(high_scores.scores(), &[20, 12, 7, 5, 3]);
+        assert!(high_scores.record(4));
+        assert_eq!(high_scores.scores(), &[20, 12, 7, 5, 4]);
+        assert_eq!(HighScores::parse(&high_scores.to_setting()), high_scores);
+    }
+}
@@ -1,13 +1,20 @@
 #[macro_use]
 mod browser;
-mod engine;
+mod canvas_ui;
+pub mod engine;
+mod assets;
 mod game;
+mod high_scores;
 mod state_machine;
 mod segments;
+mod sfx;
 mod sound;
+pub mod tween;
+mod ui;
 
 use engine::GameLoop;
 use game::WalkTheDog;
+use ui::{Label, Panel};
 use wasm_bindgen::prelude::*;
 
 // This is like the `main` function, except for JavaScript.
@@ -18,9 +25,16 @@ pub fn main_js() -> Result<(), JsValue> {
     browser::spawn_local(async move {
         let game = WalkTheDog::new();
 
-        GameLoop::start(game)
-            .await
-            .expect("Could not start game loop");
+        if let Err(err) = GameLoop::start(game).await {
+            error!("Could not start game loop {:#?}", err);
+            let message = format!("Could not start the game: {}", err);
+            let screen = Panel::<()>::new()
+                .with_class("error")
+                .child(Label::new(&message));
+            if let Err(err) = ui::show(screen) {
+                error!("Could not show error screen {:#?}", err);
+            }
+        }
     });
 
     Ok(())
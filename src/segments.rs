@@ -1,13 +1,28 @@
 use std::rc::Rc;
 use web_sys::HtmlImageElement;
 
-use crate::{engine::{Rect, SpriteSheet, Image, Point}, game::{Platform, Obstacle, Barrier}};
+use crate::{engine::{Rect, SpriteSheet, Image, Point}, game::{Platform, Obstacle, Barrier, Collectible, PowerUpPickup, Enemy, HEIGHT}, state_machine::PowerUp};
 
 const LOW_PLATFORM: i16 = 420;
 const HIGH_PLATFORM: i16 = 375;
 
 const STONE_ON_GROUND: i16 = 546;
 
+const COLLECTIBLE_SPACING: i16 = 50;
+const COLLECTIBLE_ARC: [i16; 5] = [480, 420, 390, 420, 480];
+const COLLECTIBLE_ABOVE_PLATFORM: i16 = 30;
+const POWER_UP_ABOVE_PLATFORM: i16 = 90;
+
+const OSCILLATION_AMPLITUDE: i16 = 60;
+const OSCILLATION_PERIOD: u16 = 180;
+
+const ENEMY_HEIGHT: i16 = 56;
+const ENEMY_ON_GROUND: i16 = HEIGHT - ENEMY_HEIGHT;
+const WALKER_SPEED: i16 = 1;
+const FLYER_HEIGHT: i16 = 420;
+const FLYER_AMPLITUDE: i16 = 50;
+const FLYER_PERIOD: u16 = 120;
+
 const FLOATING_PLATFORM_SPRITES: [&str; 3] = ["13.png", "14.png", "15.png"];
 const PLATFORM_WIDTH: i16 = 384;
 const PLATFORM_HEIGHT: i16 = 93;
@@ -81,6 +96,224 @@ pub fn platform_and_stone(
     ]
 }
 
+pub fn stone_with_collectibles(
+    stone: HtmlImageElement,
+    offset_x: i16,
+) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_STONE_OFFSET: i16 = 300;
+    const INITIAL_COLLECTIBLE_OFFSET: i16 = 210;
+
+    let mut obstacles: Vec<Box<dyn Obstacle>> = vec![Box::new(Barrier::new(Image::new(
+        stone,
+        Point {
+            x: offset_x + INITIAL_STONE_OFFSET,
+            y: STONE_ON_GROUND,
+        },
+    )))];
+    obstacles.append(&mut collectibles_along(
+        &COLLECTIBLE_ARC,
+        offset_x + INITIAL_COLLECTIBLE_OFFSET,
+    ));
+    obstacles
+}
+
+pub fn platform_with_power_up(
+    sprite_sheet: Rc<SpriteSheet>,
+    offset_x: i16,
+    power_up: PowerUp,
+) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_PLATFORM_OFFSET: i16 = 200;
+    const COLLECTIBLES_ON_PLATFORM: [i16; 6] = [LOW_PLATFORM - COLLECTIBLE_ABOVE_PLATFORM; 6];
+
+    let platform_x = offset_x + INITIAL_PLATFORM_OFFSET;
+    let mut obstacles: Vec<Box<dyn Obstacle>> = vec![
+        Box::new(create_floating_platform(
+            sprite_sheet,
+            Point {
+                x: platform_x,
+                y: LOW_PLATFORM,
+            },
+        )),
+        Box::new(PowerUpPickup::new(
+            power_up,
+            Point {
+                x: platform_x + PLATFORM_WIDTH / 2,
+                y: LOW_PLATFORM - POWER_UP_ABOVE_PLATFORM,
+            },
+        )),
+    ];
+    obstacles.append(&mut collectibles_along(
+        &COLLECTIBLES_ON_PLATFORM,
+        platform_x + PLATFORM_EDGE_WIDTH,
+    ));
+    obstacles
+}
+
+pub fn moving_platform_and_stone(
+    stone: HtmlImageElement,
+    sprite_sheet: Rc<SpriteSheet>,
+    offset_x: i16,
+) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_STONE_OFFSET: i16 = 350;
+    const INITIAL_PLATFORM_OFFSET: i16 = 200;
+
+    vec![
+        Box::new(Barrier::new(Image::new(
+            stone,
+            Point {
+                x: offset_x + INITIAL_STONE_OFFSET,
+                y: STONE_ON_GROUND,
+            },
+        ))),
+        Box::new(
+            create_floating_platform(
+                sprite_sheet,
+                Point {
+                    x: offset_x + INITIAL_PLATFORM_OFFSET,
+                    y: HIGH_PLATFORM,
+                },
+            )
+            .oscillating(OSCILLATION_AMPLITUDE, OSCILLATION_PERIOD),
+        ),
+    ]
+}
+
+pub fn crumbling_platform_and_stone(
+    stone: HtmlImageElement,
+    sprite_sheet: Rc<SpriteSheet>,
+    offset_x: i16,
+) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_STONE_OFFSET: i16 = 450;
+    const INITIAL_PLATFORM_OFFSET: i16 = 200;
+
+    vec![
+        Box::new(Barrier::new(Image::new(
+            stone,
+            Point {
+                x: offset_x + INITIAL_STONE_OFFSET,
+                y: STONE_ON_GROUND,
+            },
+        ))),
+        Box::new(
+            create_floating_platform(
+                sprite_sheet,
+                Point {
+                    x: offset_x + INITIAL_PLATFORM_OFFSET,
+                    y: HIGH_PLATFORM,
+                },
+            )
+            .crumbling(),
+        ),
+    ]
+}
+
+pub fn one_way_platform(
+    sprite_sheet: Rc<SpriteSheet>,
+    offset_x: i16,
+) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_PLATFORM_OFFSET: i16 = 200;
+    const COLLECTIBLES_ON_PLATFORM: [i16; 6] = [LOW_PLATFORM - COLLECTIBLE_ABOVE_PLATFORM; 6];
+
+    let platform_x = offset_x + INITIAL_PLATFORM_OFFSET;
+    let mut obstacles: Vec<Box<dyn Obstacle>> = vec![Box::new(
+        create_floating_platform(
+            sprite_sheet,
+            Point {
+                x: platform_x,
+                y: LOW_PLATFORM,
+            },
+        )
+        .one_way(),
+    )];
+    obstacles.append(&mut collectibles_along(
+        &COLLECTIBLES_ON_PLATFORM,
+        platform_x + PLATFORM_EDGE_WIDTH,
+    ));
+    obstacles
+}
+
+pub fn walker_on_platform(
+    sprite_sheet: Rc<SpriteSheet>,
+    enemy_sheet: Rc<SpriteSheet>,
+    offset_x: i16,
+) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_PLATFORM_OFFSET: i16 = 200;
+    const PATROL: i16 = PLATFORM_WIDTH - PLATFORM_EDGE_WIDTH * 2 - 64;
+
+    let platform_x = offset_x + INITIAL_PLATFORM_OFFSET;
+    vec![
+        Box::new(create_floating_platform(
+            sprite_sheet,
+            Point {
+                x: platform_x,
+                y: LOW_PLATFORM,
+            },
+        )),
+        Box::new(Enemy::walker(
+            enemy_sheet,
+            Point {
+                x: platform_x + PLATFORM_EDGE_WIDTH,
+                y: LOW_PLATFORM - ENEMY_HEIGHT,
+            },
+            PATROL,
+            WALKER_SPEED,
+        )),
+    ]
+}
+
+pub fn hopper_and_stone(
+    stone: HtmlImageElement,
+    enemy_sheet: Rc<SpriteSheet>,
+    offset_x: i16,
+) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_HOPPER_OFFSET: i16 = 200;
+    const INITIAL_STONE_OFFSET: i16 = 500;
+
+    vec![
+        Box::new(Enemy::hopper(
+            enemy_sheet,
+            Point {
+                x: offset_x + INITIAL_HOPPER_OFFSET,
+                y: ENEMY_ON_GROUND,
+            },
+        )),
+        Box::new(Barrier::new(Image::new(
+            stone,
+            Point {
+                x: offset_x + INITIAL_STONE_OFFSET,
+                y: STONE_ON_GROUND,
+            },
+        ))),
+    ]
+}
+
+pub fn flyer(enemy_sheet: Rc<SpriteSheet>, offset_x: i16) -> Vec<Box<dyn Obstacle>> {
+    const INITIAL_FLYER_OFFSET: i16 = 300;
+
+    vec![Box::new(Enemy::flyer(
+        enemy_sheet,
+        Point {
+            x: offset_x + INITIAL_FLYER_OFFSET,
+            y: FLYER_HEIGHT,
+        },
+        FLYER_AMPLITUDE,
+        FLYER_PERIOD,
+    ))]
+}
+
+fn collectibles_along(heights: &[i16], offset_x: i16) -> Vec<Box<dyn Obstacle>> {
+    heights
+        .iter()
+        .enumerate()
+        .map(|(index, y)| -> Box<dyn Obstacle> {
+            Box::new(Collectible::new(Point {
+                x: offset_x + COLLECTIBLE_SPACING * index as i16,
+                y: *y,
+            }))
+        })
+        .collect()
+}
+
 fn create_floating_platform(
     sprite_sheet: Rc<SpriteSheet>,
     position: Point,
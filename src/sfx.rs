@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use rand::prelude::*;
+
+use crate::{
+    assets::{Assets, Handle},
+    engine::{Audio, Point, Sound},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SoundCue {
+    Jump,
+    Land,
+    Slide,
+    KnockOut,
+    Collect,
+    Stomp,
+    EnemyNear,
+}
+
+pub trait AudioBackend {
+    fn play_sound(
+        &mut self,
+        sound: Handle<Sound>,
+        gain: f32,
+        rate: f32,
+        position: Option<Point>,
+    ) -> Result<u16>;
+}
+
+pub struct WebAudio<'a> {
+    pub audio: &'a Audio,
+    pub assets: &'a Assets,
+}
+
+#[cfg(test)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PlayedSound {
+    pub tick: u32,
+    pub sound: Handle<Sound>,
+    pub position: Option<Point>,
+}
+
+#[cfg(test)]
+#[derive(Default)]
+pub struct RecordingAudio {
+    tick: u32,
+    played: Vec<PlayedSound>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Variation {
+    pub sound: Handle<Sound>,
+    pub rate: f32,
+    pub gain: f32,
+}
+
+pub struct SoundEvent {
+    sounds: Vec<Handle<Sound>>,
+    rate: (f32, f32),
+    gain: (f32, f32),
+    cooldown: u16,
+    max_voices: usize,
+    cooling: u16,
+    voices: Vec<u16>,
+}
+
+#[derive(Default)]
+pub struct SoundEvents {
+    events: HashMap<SoundCue, SoundEvent>,
+}
+
+impl SoundEvent {
+    pub fn new(sounds: Vec<Handle<Sound>>) -> Self {
+        SoundEvent {
+            sounds,
+            rate: (1.0, 1.0),
+            gain: (1.0, 1.0),
+            cooldown: 0,
+            max_voices: usize::MAX,
+            cooling: 0,
+            voices: vec![],
+        }
+    }
+
+    pub fn rate(mut self, min: f32, max: f32) -> Self {
+        self.rate = (min, max);
+        self
+    }
+
+    pub fn gain(mut self, min: f32, max: f32) -> Self {
+        self.gain = (min, max);
+        self
+    }
+
+    pub fn cooldown(mut self, frames: u16) -> Self {
+        self.cooldown = frames;
+        self
+    }
+
+    pub fn max_voices(mut self, voices: usize) -> Self {
+        self.max_voices = voices;
+        self
+    }
+
+    pub fn update(&mut self) {
+        self.cooling = self.cooling.saturating_sub(1);
+        self.voices.iter_mut().for_each(|frames| *frames -= 1);
+        self.voices.retain(|frames| *frames > 0);
+    }
+
+    pub fn trigger(&mut self, rng: &mut impl Rng) -> Option<Variation> {
+        if self.cooling > 0 || self.voices.len() >= self.max_voices {
+            return None;
+        }
+
+        let sound = *self.sounds.choose(rng)?;
+        self.cooling = self.cooldown;
+        Some(Variation {
+            sound,
+            rate: pick(rng, self.rate),
+            gain: pick(rng, self.gain),
+        })
+    }
+
+    pub fn voice_started(&mut self, frames: u16) {
+        self.voices.push(frames.max(1));
+    }
+}
+
+impl SoundEvents {
+    pub fn with(mut self, cue: SoundCue, event: SoundEvent) -> Self {
+        self.events.insert(cue, event);
+        self
+    }
+
+    pub fn update(&mut self) {
+        self.events.values_mut().for_each(SoundEvent::update);
+    }
+
+    pub fn play(
+        &mut self,
+        cue: SoundCue,
+        position: Option<Point>,
+        backend: &mut impl AudioBackend,
+    ) -> Result<()> {
+        if let Some(event) = self.events.get_mut(&cue) {
+            if let Some(variation) = event.trigger(&mut thread_rng()) {
+                let frames =
+                    backend.play_sound(variation.sound, variation.gain, variation.rate, position)?;
+                event.voice_started(frames);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> AudioBackend for WebAudio<'a> {
+    fn play_sound(
+        &mut self,
+        sound: Handle<Sound>,
+        gain: f32,
+        rate: f32,
+        position: Option<Point>,
+    ) -> Result<u16> {
+        let sound = self.assets.get(sound)?;
+        self.audio.play_sound(&sound, gain, rate, position)?;
+        Ok(sound.duration_in_frames(rate))
+    }
+}
+
+#[cfg(test)]
+impl RecordingAudio {
+    pub const VOICE_FRAMES: u16 = 10;
+
+    pub fn tick(&mut self) {
+        self.tick += 1;
+    }
+
+    pub fn played(&self) -> &[PlayedSound] {
+        &self.played
+    }
+
+    pub fn count(&self, sound: Handle<Sound>) -> usize {
+        self.played.iter().filter(|played| played.sound == sound).count()
+    }
+}
+
+#[cfg(test)]
+impl AudioBackend for RecordingAudio {
+    fn play_sound(
+        &mut self,
+        sound: Handle<Sound>,
+        _gain: f32,
+        _rate: f32,
+        position: Option<Point>,
+    ) -> Result<u16> {
+        self.played.push(PlayedSound {
+            tick: self.tick,
+            sound,
+            position,
+        });
+        Ok(RecordingAudio::VOICE_FRAMES)
+    }
+}
+
+fn pick(rng: &mut impl Rng, (min, max): (f32, f32)) -> f32 {
+    if min < max {
+        rng.gen_range(min..max)
+    } else {
+        min
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::{mock::StepRng, StdRng};
+
+    #[test]
+    fn cooldown_and_voice_limit_stop_rapid_triggers_stacking() {
+        let mut assets = Assets::new();
+        let sounds = vec![assets.register("a.mp3"), assets.register("b.mp3")];
+        let mut event = SoundEvent::new(sounds.clone())
+            .rate(0.9, 1.1)
+            .gain(0.5, 0.5)
+            .cooldown(2)
+            .max_voices(1);
+        let mut rng = StepRng::new(0, 1);
+
+        let variation = event.trigger(&mut rng).expect("first trigger plays");
+        assert!(sounds.contains(&variation.sound));
+        assert!(variation.rate >= 0.9 && variation.rate < 1.1);
+        assert_eq!(variation.gain, 0.5);
+        event.voice_started(4);
+
+        event.update();
+        assert_eq!(event.trigger(&mut rng), None);
+        event.update();
+        assert_eq!(event.trigger(&mut rng), None, "voice still playing");
+        event.update();
+        event.update();
+        assert!(event.trigger(&mut rng).is_some());
+    }
+
+    #[test]
+    fn triggers_pick_between_the_event_sounds() {
+        let mut assets = Assets::new();
+        let sounds = vec![assets.register("a.mp3"), assets.register("b.mp3")];
+        let mut event = SoundEvent::new(sounds.clone());
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let picked: Vec<Handle<Sound>> = (0..20)
+            .filter_map(|_| event.trigger(&mut rng))
+            .map(|variation| variation.sound)
+            .collect();
+
+        assert_eq!(picked.len(), 20);
+        assert!(sounds.iter().all(|sound| picked.contains(sound)));
+    }
+}
@@ -1,27 +1,302 @@
 use anyhow::{anyhow, Result};
 use js_sys::ArrayBuffer;
+use serde::{Deserialize, Serialize};
 use wasm_bindgen::JsCast;
 use wasm_bindgen_futures::JsFuture;
-use web_sys::{AudioContext, AudioBufferSourceNode, AudioDestinationNode, AudioNode, AudioBuffer};
+use web_sys::{AudioContext, AudioBufferSourceNode, AudioNode, AudioBuffer, GainNode, StereoPannerNode};
 
-pub enum LOOPING {
-    NO,
-    YES,
+const PAN_RANGE: f32 = 600.0;
+const HEARING_NEAR: f32 = 150.0;
+const HEARING_FAR: f32 = 900.0;
+const VOLUME_STEP: f32 = 0.25;
+
+pub enum Looping {
+    No,
+    Yes,
+}
+
+#[derive(Clone, Copy)]
+pub enum Bus {
+    Music,
+    Effects,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Volumes {
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+    pub muted: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Voice {
+    pub volume: f32,
+    pub rate: f32,
+    pub pan: f32,
+}
+
+pub struct Track {
+    source: AudioBufferSourceNode,
+    gain: GainNode,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+pub enum Waveform {
+    Square,
+    Sawtooth,
+    Sine,
+    Noise,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct SynthParams {
+    pub wave: Waveform,
+    pub frequency: f32,
+    #[serde(default)]
+    pub slide: f32,
+    #[serde(default)]
+    pub min_frequency: f32,
+    #[serde(default)]
+    pub attack: f32,
+    #[serde(default)]
+    pub sustain: f32,
+    #[serde(default)]
+    pub decay: f32,
+    #[serde(default = "default_duty")]
+    pub duty: f32,
+    #[serde(default = "default_volume")]
+    pub volume: f32,
+}
+
+#[derive(Clone)]
+pub struct Mixer {
+    master: GainNode,
+    music: GainNode,
+    effects: GainNode,
+}
+
+impl Default for Volumes {
+    fn default() -> Self {
+        Volumes {
+            master: 1.0,
+            music: 0.6,
+            effects: 1.0,
+            muted: false,
+        }
+    }
+}
+
+impl Volumes {
+    pub fn master_gain(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.master.clamp(0.0, 1.0)
+        }
+    }
+
+    pub fn bus_gain(&self, bus: Bus) -> f32 {
+        let volume = match bus {
+            Bus::Music => self.music,
+            Bus::Effects => self.effects,
+        };
+        volume.clamp(0.0, 1.0)
+    }
+}
+
+pub fn next_volume(volume: f32) -> f32 {
+    if volume >= 1.0 {
+        0.0
+    } else {
+        ((volume / VOLUME_STEP).floor() + 1.0) * VOLUME_STEP
+    }
+}
+
+fn default_duty() -> f32 {
+    0.5
+}
+
+fn default_volume() -> f32 {
+    0.5
+}
+
+impl SynthParams {
+    pub fn duration(&self) -> f32 {
+        self.attack.max(0.0) + self.sustain.max(0.0) + self.decay.max(0.0)
+    }
+
+    fn envelope(&self, t: f32) -> f32 {
+        if t < self.attack {
+            t / self.attack
+        } else if t < self.attack + self.sustain {
+            1.0
+        } else if self.decay > 0.0 {
+            (1.0 - (t - self.attack - self.sustain) / self.decay).max(0.0)
+        } else {
+            0.0
+        }
+    }
+}
+
+pub fn synthesize(params: &SynthParams, sample_rate: f32) -> Vec<f32> {
+    let length = (params.duration() * sample_rate).ceil() as usize;
+    let min_frequency = params.min_frequency.max(1.0);
+    let mut frequency = params.frequency.max(min_frequency);
+    let mut phase = 0.0;
+    let mut seed: u32 = 0x9E37_79B9;
+    let mut noise = 0.0;
+
+    (0..length)
+        .map(|index| {
+            let sample = match params.wave {
+                Waveform::Square => {
+                    if phase < params.duty {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Waveform::Sawtooth => 1.0 - 2.0 * phase,
+                Waveform::Sine => (phase * std::f32::consts::TAU).sin(),
+                Waveform::Noise => noise,
+            };
+
+            phase += frequency / sample_rate;
+            if phase >= 1.0 {
+                phase -= phase.floor();
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                noise = seed as f32 / u32::MAX as f32 * 2.0 - 1.0;
+            }
+            frequency = (frequency + params.slide / sample_rate).max(min_frequency);
+
+            sample * params.envelope(index as f32 / sample_rate) * params.volume
+        })
+        .collect()
+}
+
+pub fn create_buffer(ctx: &AudioContext, samples: &mut [f32], sample_rate: f32) -> Result<AudioBuffer> {
+    let buffer = ctx
+        .create_buffer(1, samples.len().max(1) as u32, sample_rate)
+        .map_err(|err| anyhow!("Could not create audio buffer {:#?}", err))?;
+    buffer
+        .copy_to_channel(samples, 0)
+        .map_err(|err| anyhow!("Could not copy samples into audio buffer {:#?}", err))?;
+    Ok(buffer)
+}
+
+impl Mixer {
+    pub fn new(ctx: &AudioContext) -> Result<Self> {
+        let master = create_gain(ctx, 1.0)?;
+        connect_with_audio_node(&master, &ctx.destination())?;
+        let music = create_gain(ctx, 1.0)?;
+        connect_with_audio_node(&music, &master)?;
+        let effects = create_gain(ctx, 1.0)?;
+        connect_with_audio_node(&effects, &master)?;
+
+        Ok(Mixer {
+            master,
+            music,
+            effects,
+        })
+    }
+
+    pub fn apply(&self, volumes: &Volumes) {
+        self.master.gain().set_value(volumes.master_gain());
+        self.music.gain().set_value(volumes.bus_gain(Bus::Music));
+        self.effects.gain().set_value(volumes.bus_gain(Bus::Effects));
+    }
+
+    pub fn bus(&self, bus: Bus) -> &GainNode {
+        match bus {
+            Bus::Music => &self.music,
+            Bus::Effects => &self.effects,
+        }
+    }
 }
 
 pub fn create_audio_context() -> Result<AudioContext> {
     AudioContext::new().map_err(|err| anyhow!("Could not create audio context: {:#?}", err))
 }
 
-pub fn play_sound(ctx: &AudioContext, buffer: &AudioBuffer, looping: LOOPING) -> Result<()> {
-    let track_source = create_track_source(ctx, buffer)?;
-    if matches!(looping, LOOPING::YES) {
+pub async fn resume_audio_context(ctx: &AudioContext) -> Result<()> {
+    JsFuture::from(
+        ctx.resume()
+            .map_err(|err| anyhow!("Could not resume audio context {:#?}", err))?,
+    )
+    .await
+    .map(|_| ())
+    .map_err(|err| anyhow!("Audio context refused to resume {:#?}", err))
+}
+
+impl Track {
+    pub fn fade_to(&self, ctx: &AudioContext, volume: f32, seconds: f64) -> Result<()> {
+        let gain = self.gain.gain();
+        let now = ctx.current_time();
+        gain.cancel_scheduled_values(now)
+            .and_then(|gain| gain.set_value_at_time(gain.value(), now))
+            .and_then(|gain| gain.linear_ramp_to_value_at_time(volume.max(0.0), now + seconds))
+            .map(|_| ())
+            .map_err(|err| anyhow!("Could not fade track {:#?}", err))
+    }
+
+    pub fn stop(&self, ctx: &AudioContext, delay: f64) -> Result<()> {
+        self.source
+            .stop_with_when(ctx.current_time() + delay)
+            .map_err(|err| anyhow!("Could not stop track {:#?}", err))
+    }
+}
+
+pub fn spatialize(dx: f32, dy: f32) -> (f32, f32) {
+    let pan = (dx / PAN_RANGE).clamp(-1.0, 1.0);
+    let distance = (dx * dx + dy * dy).sqrt();
+    let attenuation = (distance - HEARING_NEAR) / (HEARING_FAR - HEARING_NEAR);
+
+    (pan, 1.0 - attenuation.clamp(0.0, 1.0))
+}
+
+pub fn play_sound(
+    ctx: &AudioContext,
+    buffer: &AudioBuffer,
+    destination: &AudioNode,
+    voice: Voice,
+    looping: Looping,
+) -> Result<Track> {
+    play_sound_from(ctx, buffer, destination, voice, looping, 0.0)
+}
+
+pub fn play_sound_from(
+    ctx: &AudioContext,
+    buffer: &AudioBuffer,
+    destination: &AudioNode,
+    voice: Voice,
+    looping: Looping,
+    offset: f64,
+) -> Result<Track> {
+    let gain = create_gain(ctx, voice.volume.max(0.0))?;
+    if voice.pan == 0.0 {
+        connect_with_audio_node(&gain, destination)?;
+    } else {
+        let panner = create_stereo_panner(ctx, voice.pan)?;
+        connect_with_audio_node(&panner, destination)?;
+        connect_with_audio_node(&gain, &panner)?;
+    }
+    let track_source = create_track_source(ctx, buffer, &gain)?;
+    track_source.playback_rate().set_value(voice.rate);
+    if matches!(looping, Looping::Yes) {
         track_source.set_loop(true);
     }
 
     track_source
-        .start()
-        .map_err(|err| anyhow!("Could not start sound!{:#?}", err))
+        .start_with_when_and_grain_offset(0.0, offset)
+        .map_err(|err| anyhow!("Could not start sound!{:#?}", err))?;
+
+    Ok(Track {
+        source: track_source,
+        gain,
+    })
 }
 
 pub async fn decode_audio_data(
@@ -29,7 +304,7 @@ pub async fn decode_audio_data(
     array_buffer: &ArrayBuffer,
 ) -> Result<AudioBuffer> {
     JsFuture::from(
-        ctx.decode_audio_data(&array_buffer)
+        ctx.decode_audio_data(array_buffer)
             .map_err(|err| anyhow!("Could not decode audio from array buffer {:#?}", err))?,
     )
         .await
@@ -43,18 +318,125 @@ fn create_buffer_source(ctx: &AudioContext) -> Result<AudioBufferSourceNode> {
         .map_err(|err| anyhow!("Error creating buffer source {:#?}", err))
 }
 
-fn connect_with_audio_node(
-    buffer_source: &AudioBufferSourceNode,
-    destination: &AudioDestinationNode,
-) -> Result<AudioNode> {
-    buffer_source
-        .connect_with_audio_node(&destination)
+fn create_gain(ctx: &AudioContext, gain: f32) -> Result<GainNode> {
+    let node = ctx
+        .create_gain()
+        .map_err(|err| anyhow!("Error creating gain node {:#?}", err))?;
+    node.gain().set_value(gain);
+    Ok(node)
+}
+
+fn create_stereo_panner(ctx: &AudioContext, pan: f32) -> Result<StereoPannerNode> {
+    let node = ctx
+        .create_stereo_panner()
+        .map_err(|err| anyhow!("Error creating stereo panner {:#?}", err))?;
+    node.pan().set_value(pan);
+    Ok(node)
+}
+
+fn connect_with_audio_node(source: &AudioNode, destination: &AudioNode) -> Result<AudioNode> {
+    source
+        .connect_with_audio_node(destination)
         .map_err(|err| anyhow!("Error connecting audio source to destination {:#?}", err))
 }
 
-fn create_track_source(ctx: &AudioContext, buffer: &AudioBuffer) -> Result<AudioBufferSourceNode> {
+fn create_track_source(
+    ctx: &AudioContext,
+    buffer: &AudioBuffer,
+    destination: &AudioNode,
+) -> Result<AudioBufferSourceNode> {
     let track_source = create_buffer_source(ctx)?;
-    track_source.set_buffer(Some(&buffer));
-    connect_with_audio_node(&track_source, &ctx.destination())?;
+    track_source.set_buffer(Some(buffer));
+    connect_with_audio_node(&track_source, destination)?;
     Ok(track_source)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn muting_silences_master_and_volumes_are_clamped() {
+        let volumes = Volumes {
+            master: 1.5,
+            music: -0.5,
+            effects: 0.25,
+            muted: false,
+        };
+
+        assert_eq!(volumes.master_gain(), 1.0);
+        assert_eq!(volumes.bus_gain(Bus::Music), 0.0);
+        assert_eq!(volumes.bus_gain(Bus::Effects), 0.25);
+        assert_eq!(Volumes { muted: true, ..volumes }.master_gain(), 0.0);
+    }
+
+    #[test]
+    fn volume_steps_wrap_back_to_silence() {
+        assert_eq!(next_volume(0.0), 0.25);
+        assert_eq!(next_volume(0.6), 0.75);
+        assert_eq!(next_volume(1.0), 0.0);
+    }
+
+    #[test]
+    fn sounds_to_the_right_pan_right_and_fade_with_distance() {
+        assert_eq!(spatialize(0.0, 0.0), (0.0, 1.0));
+
+        let (pan, gain) = spatialize(300.0, 0.0);
+        assert_eq!(pan, 0.5);
+        assert!(gain < 1.0 && gain > 0.0);
+
+        let (pan, gain) = spatialize(-2000.0, 0.0);
+        assert_eq!((pan, gain), (-1.0, 0.0));
+    }
+
+    fn blip(wave: Waveform) -> SynthParams {
+        SynthParams {
+            wave,
+            frequency: 440.0,
+            slide: 0.0,
+            min_frequency: 0.0,
+            attack: 0.01,
+            sustain: 0.05,
+            decay: 0.04,
+            duty: 0.5,
+            volume: 0.5,
+        }
+    }
+
+    fn crossings(samples: &[f32]) -> usize {
+        samples
+            .windows(2)
+            .filter(|pair| pair[0] <= 0.0 && pair[1] > 0.0)
+            .count()
+    }
+
+    #[test]
+    fn synthesized_samples_follow_the_envelope() {
+        [Waveform::Square, Waveform::Sawtooth, Waveform::Sine, Waveform::Noise]
+            .iter()
+            .for_each(|wave| {
+                let samples = synthesize(&blip(*wave), 8000.0);
+
+                assert_eq!(samples.len(), 800, "{:?}", wave);
+                assert_eq!(samples[0], 0.0, "{:?}", wave);
+                assert!(samples.iter().all(|sample| sample.abs() <= 0.5), "{:?}", wave);
+                assert!(samples[799].abs() < 0.01, "{:?}", wave);
+            });
+        assert_eq!(synthesize(&blip(Waveform::Noise), 8000.0), synthesize(&blip(Waveform::Noise), 8000.0));
+    }
+
+    #[test]
+    fn slide_sweeps_the_frequency_down_to_the_minimum() {
+        let params = SynthParams {
+            slide: -4000.0,
+            min_frequency: 110.0,
+            sustain: 0.5,
+            ..blip(Waveform::Square)
+        };
+        let samples = synthesize(&params, 8000.0);
+        let (start, end) = samples.split_at(800);
+
+        assert!(crossings(&start[..400]) > crossings(&end[end.len() - 400..]));
+        assert!(crossings(&end[end.len() - 800..]) >= 10);
+    }
+}
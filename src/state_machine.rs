@@ -1,6 +1,6 @@
 
 mod red_hat_boy_states {
-    use crate::{engine::{Point, Audio, Sound}, game::HEIGHT};
+    use crate::{engine::{self, Point, Sheet, Animation, AnimationEvent, Animations, Playback}, game::HEIGHT, sfx::SoundCue};
     const FLOOR: i16 = 475;
     const PLAYER_HEIGHT: i16 = HEIGHT - FLOOR;
     const STARTING_POINT: i16 = -20;
@@ -13,15 +13,37 @@ mod red_hat_boy_states {
     const SLIDING_FRAME_NAME: &str = "Slide";
     const JUMPING_FRAME_NAME: &str = "Jump";
     const FALLING_FRAME_NAME: &str = "Dead";
+    const HURT_FRAME_NAME: &str = "Hurt";
 
-    const IDLE_FRAMES: u8 = 29;
-    const RUNNING_FRAMES: u8 = 23;
-    const SLIDING_FRAMES: u8 = 14;
-    const JUMPING_FRAMES: u8 = 35;
-    const FALLING_FRAMES: u8 = 29;
+    const FRAME_DURATION: u16 = 3;
 
     const RUNNING_SPEED: i16 = 4;
 
+    const POWER_UP_DURATION: u16 = 600;
+    const INVULNERABLE_DURATION: u16 = 60;
+    const HURT_INVULNERABLE_DURATION: u16 = 120;
+    const FLICKER_RATE: u16 = 4;
+
+    const STARTING_LIVES: u8 = 3;
+    const HURT_BOUNCE: i16 = -8;
+    const STOMP_BOUNCE: i16 = -15;
+
+    #[derive(Copy, Clone, PartialEq)]
+    pub enum PowerUp {
+        Shield,
+        Magnet,
+        DoubleJump,
+    }
+
+    #[derive(Copy, Clone, Default)]
+    pub struct Modifiers {
+        shield: u16,
+        magnet: u16,
+        double_jump: u16,
+        invulnerable: u16,
+        air_jump_spent: bool,
+    }
+
     #[derive(Clone)]
     pub struct RedHatBoyState<S> {
         context: RedHatBoyContext,
@@ -30,11 +52,14 @@ mod red_hat_boy_states {
 
     #[derive(Clone)]
     pub struct RedHatBoyContext {
-        pub frame: u8,
+        pub animation: Animation,
+        animations: Animations,
         pub position: Point,
         pub velocity: Point,
-        pub audio: Audio,
-        pub jump_sound: Sound,
+        sounds: Vec<SoundCue>,
+        pub modifiers: Modifiers,
+        pub collected: u16,
+        pub lives: u8,
     }
 
     #[derive(Copy, Clone)]
@@ -55,44 +80,175 @@ mod red_hat_boy_states {
     #[derive(Copy, Clone)]
     pub struct KnockedOut;
 
+    #[derive(Copy, Clone)]
+    pub struct Hurt;
+
     impl<S> RedHatBoyState<S> {
         pub fn context(&self) -> &RedHatBoyContext {
             &self.context
         }
+
+        pub fn power_up(mut self, power_up: PowerUp) -> Self {
+            self.context.modifiers = self.context.modifiers.activate(power_up);
+            self
+        }
+
+        pub fn collect(mut self) -> Self {
+            self.context.collected += 1;
+            self
+        }
+
+        pub fn absorb_hit(mut self) -> Self {
+            self.context.modifiers = self.context.modifiers.absorb_hit();
+            self
+        }
+
+        pub fn shift(mut self, x: i16) -> Self {
+            self.context.position.x += x;
+            self
+        }
+
+        fn take_hit(self) -> KnockOutEndState {
+            if self.context.lives > 1 {
+                KnockOutEndState::Hurt(RedHatBoyState {
+                    context: self.context.hurt(),
+                    _state: Hurt {},
+                })
+            } else {
+                KnockOutEndState::Falling(RedHatBoyState {
+                    context: self.context.fall(),
+                    _state: Falling {},
+                })
+            }
+        }
+
+        pub fn take_sounds(&mut self) -> Vec<SoundCue> {
+            std::mem::take(&mut self.context.sounds)
+        }
+    }
+
+    impl Modifiers {
+        pub fn remaining(&self, power_up: PowerUp) -> u16 {
+            match power_up {
+                PowerUp::Shield => self.shield,
+                PowerUp::Magnet => self.magnet,
+                PowerUp::DoubleJump => self.double_jump,
+            }
+        }
+
+        pub fn progress(&self, power_up: PowerUp) -> f32 {
+            f32::from(self.remaining(power_up)) / f32::from(POWER_UP_DURATION)
+        }
+
+        pub fn is_active(&self, power_up: PowerUp) -> bool {
+            self.remaining(power_up) > 0
+        }
+
+        pub fn is_protected(&self) -> bool {
+            self.shield > 0 || self.invulnerable > 0
+        }
+
+        pub fn is_flickering(&self) -> bool {
+            self.invulnerable > 0 && (self.invulnerable / FLICKER_RATE) % 2 == 0
+        }
+
+        pub fn can_air_jump(&self) -> bool {
+            self.double_jump > 0 && !self.air_jump_spent
+        }
+
+        fn activate(mut self, power_up: PowerUp) -> Self {
+            match power_up {
+                PowerUp::Shield => self.shield = POWER_UP_DURATION,
+                PowerUp::Magnet => self.magnet = POWER_UP_DURATION,
+                PowerUp::DoubleJump => self.double_jump = POWER_UP_DURATION,
+            }
+            self
+        }
+
+        fn absorb_hit(mut self) -> Self {
+            if self.shield > 0 {
+                self.shield = 0;
+                self.invulnerable = INVULNERABLE_DURATION;
+            }
+            self
+        }
+
+        fn make_invulnerable(mut self, duration: u16) -> Self {
+            self.invulnerable = duration;
+            self
+        }
+
+        fn spend_air_jump(mut self) -> Self {
+            self.air_jump_spent = true;
+            self
+        }
+
+        fn reset_air_jump(mut self) -> Self {
+            self.air_jump_spent = false;
+            self
+        }
+
+        fn tick(mut self) -> Self {
+            self.shield = self.shield.saturating_sub(1);
+            self.magnet = self.magnet.saturating_sub(1);
+            self.double_jump = self.double_jump.saturating_sub(1);
+            self.invulnerable = self.invulnerable.saturating_sub(1);
+            self
+        }
+    }
+
+    pub fn red_hat_boy_animations(sheet: &Sheet) -> Animations {
+        engine::animations(
+            sheet,
+            &[
+                (IDLE_FRAME_NAME, FRAME_DURATION, Playback::Loop),
+                (RUNNING_FRAME_NAME, FRAME_DURATION, Playback::Loop),
+                (SLIDING_FRAME_NAME, FRAME_DURATION, Playback::Once),
+                (JUMPING_FRAME_NAME, FRAME_DURATION, Playback::Loop),
+                (FALLING_FRAME_NAME, FRAME_DURATION, Playback::Once),
+                (HURT_FRAME_NAME, FRAME_DURATION, Playback::Once),
+            ],
+        )
     }
 
     impl RedHatBoyContext {
-        pub fn update(mut self, frame_count: u8) -> Self {
+        pub fn update(self) -> Self {
+            self.animate().0
+        }
+
+        fn animate(mut self) -> (Self, Option<AnimationEvent>) {
             if self.velocity.y < TERMINAL_VELOCITY {
                 self.velocity.y += GRAVITY;
             }
             // log!("{}", self.velocity.y);
 
-            if self.frame < frame_count {
-                self.frame += 1;
-            } else {
-                self.frame = 0;
-            }
+            let event = self.animation.update();
 
-            // self.position.x += self.velocity.x;
+            self.position.x += self.velocity.x;
             self.position.y += self.velocity.y;
 
             if self.position.y > FLOOR {
                 self.position.y = FLOOR;
             }
 
-            self
+            self.modifiers = self.modifiers.tick();
+
+            (self, event)
         }
 
-        fn play_jump_sound(self) -> Self {
-            if let Err(err) = self.audio.play_sound(&self.jump_sound) {
-                log!("Error playing jump sound {:#?}", err);
-            }
+        pub fn feet(&self) -> i16 {
+            self.position.y + PLAYER_HEIGHT
+        }
+
+        fn cue(mut self, cue: SoundCue) -> Self {
+            self.sounds.push(cue);
             self
         }
 
-        fn reset_frame(mut self) -> Self {
-            self.frame = 0;
+        fn play(mut self, name: &str) -> Self {
+            if let Some(animation) = self.animations.get(name) {
+                self.animation = animation.clone();
+            }
             self
         }
 
@@ -112,22 +268,50 @@ mod red_hat_boy_states {
             self
         }
 
+        fn lose_life(mut self) -> Self {
+            self.lives = self.lives.saturating_sub(1);
+            self.modifiers = self.modifiers.make_invulnerable(HURT_INVULNERABLE_DURATION);
+            self
+        }
+
+        fn bounce(self) -> Self {
+            self.play(JUMPING_FRAME_NAME)
+                .set_vertical_velocity(STOMP_BOUNCE)
+        }
+
+        fn hurt(self) -> Self {
+            self.play(HURT_FRAME_NAME)
+                .lose_life()
+                .set_vertical_velocity(HURT_BOUNCE)
+        }
+
+        fn fall(self) -> Self {
+            self.play(FALLING_FRAME_NAME)
+                .lose_life()
+                .stop()
+                .cue(SoundCue::KnockOut)
+        }
+
         fn set_on(mut self, position: i16) -> Self {
             let position = position - PLAYER_HEIGHT;
             self.position.y = position;
+            self.modifiers = self.modifiers.reset_air_jump();
             self
         }
     }
 
     impl RedHatBoyState<Idle> {
-        pub fn new(audio: Audio, jump_sound: Sound) -> Self {
+        pub fn new(animations: Animations) -> Self {
             RedHatBoyState {
                 context: RedHatBoyContext {
-                    frame: 0,
+                    animation: animations[IDLE_FRAME_NAME].clone(),
+                    animations,
                     position: Point { x: STARTING_POINT, y: FLOOR },
                     velocity: Point { x: 0, y: 0 },
-                    audio,
-                    jump_sound,
+                    sounds: vec![],
+                    modifiers: Modifiers::default(),
+                    collected: 0,
+                    lives: STARTING_LIVES,
                 },
                 _state: Idle {},
             }
@@ -135,35 +319,26 @@ mod red_hat_boy_states {
 
         pub fn run(self) -> RedHatBoyState<Running> {
             RedHatBoyState {
-                context: self.context.reset_frame().run_right(),
+                context: self.context.play(RUNNING_FRAME_NAME).run_right(),
                 _state: Running {},
             }
         }
 
-
-        pub fn frame_name(&self) -> &str {
-            IDLE_FRAME_NAME
-        }
-
         pub fn update(mut self) -> Self {
-            self.context = self.context.update(IDLE_FRAMES);
+            self.context = self.context.update();
             self
         }
     }
 
     impl RedHatBoyState<Running> {
-        pub fn frame_name(&self) -> &str {
-            RUNNING_FRAME_NAME
-        }
-
         pub fn update(mut self) -> Self {
-            self.context = self.context.update(RUNNING_FRAMES);
+            self.context = self.context.update();
             self
         }
 
         pub fn slide(self) -> RedHatBoyState<Sliding> {
             RedHatBoyState {
-                context: self.context.reset_frame(),
+                context: self.context.play(SLIDING_FRAME_NAME).cue(SoundCue::Slide),
                 _state: Sliding {},
             }
         }
@@ -172,9 +347,9 @@ mod red_hat_boy_states {
             RedHatBoyState {
                 context: self
                     .context
-                    .reset_frame()
+                    .play(JUMPING_FRAME_NAME)
                     .set_vertical_velocity(JUMP_SPEED)
-                    .play_jump_sound(),
+                    .cue(SoundCue::Jump),
                 _state: Jumping {},
             }
         }
@@ -186,23 +361,24 @@ mod red_hat_boy_states {
             }
         }
 
-        pub fn knock_out(self) -> RedHatBoyState<Falling> {
+        pub fn bounce(self) -> RedHatBoyState<Jumping> {
             RedHatBoyState {
-                context: self.context.reset_frame().stop(),
-                _state: Falling {},
+                context: self.context.bounce(),
+                _state: Jumping {},
             }
         }
-    }
 
-    impl RedHatBoyState<Sliding> {
-        pub fn frame_name(&self) -> &str {
-            SLIDING_FRAME_NAME
+        pub fn knock_out(self) -> KnockOutEndState {
+            self.take_hit()
         }
+    }
 
+    impl RedHatBoyState<Sliding> {
         pub fn update(mut self) -> SlidingEndState {
-            self.context = self.context.update(SLIDING_FRAMES);
+            let (context, event) = self.context.animate();
+            self.context = context;
 
-            if self.context.frame >= SLIDING_FRAMES {
+            if event == Some(AnimationEvent::Finished) {
                 SlidingEndState::Complete(self.stand())
             } else {
                 SlidingEndState::Sliding(self)
@@ -211,7 +387,7 @@ mod red_hat_boy_states {
 
         pub fn stand(self) -> RedHatBoyState<Running> {
             RedHatBoyState {
-                context: self.context.reset_frame(),
+                context: self.context.play(RUNNING_FRAME_NAME),
                 _state: Running {},
             }
         }
@@ -223,12 +399,16 @@ mod red_hat_boy_states {
             }
         }
 
-        pub fn knock_out(self) -> RedHatBoyState<Falling> {
+        pub fn bounce(self) -> RedHatBoyState<Jumping> {
             RedHatBoyState {
-                context: self.context.reset_frame().stop(),
-                _state: Falling {},
+                context: self.context.bounce(),
+                _state: Jumping {},
             }
         }
+
+        pub fn knock_out(self) -> KnockOutEndState {
+            self.take_hit()
+        }
     }
 
     pub enum SlidingEndState {
@@ -237,15 +417,11 @@ mod red_hat_boy_states {
     }
 
     impl RedHatBoyState<Jumping> {
-        pub fn frame_name(&self) -> &str {
-            JUMPING_FRAME_NAME
-        }
-
         pub fn update(mut self) -> JumpingEndState {
-            self.context = self.context.update(JUMPING_FRAMES);
+            self.context = self.context.update();
 
             if self.context.position.y >= FLOOR {
-                JumpingEndState::Complete(self.land_on(HEIGHT.into()))
+                JumpingEndState::Complete(self.land_on(HEIGHT))
             } else {
                 JumpingEndState::Jumping(self)
             }
@@ -253,17 +429,37 @@ mod red_hat_boy_states {
 
         pub fn land_on(self, position: i16) -> RedHatBoyState<Running> {
             RedHatBoyState {
-                context: self.context.reset_frame().set_on(position),
+                context: self
+                    .context
+                    .play(RUNNING_FRAME_NAME)
+                    .set_on(position)
+                    .cue(SoundCue::Land),
                 _state: Running {},
             }
         }
 
-        pub fn knock_out(self) -> RedHatBoyState<Falling> {
+        pub fn bounce(self) -> RedHatBoyState<Jumping> {
             RedHatBoyState {
-                context: self.context.reset_frame().stop(),
-                _state: Falling {},
+                context: self.context.set_vertical_velocity(STOMP_BOUNCE),
+                _state: Jumping {},
             }
         }
+
+        pub fn air_jump(mut self) -> RedHatBoyState<Jumping> {
+            self.context.modifiers = self.context.modifiers.spend_air_jump();
+            RedHatBoyState {
+                context: self
+                    .context
+                    .play(JUMPING_FRAME_NAME)
+                    .set_vertical_velocity(JUMP_SPEED)
+                    .cue(SoundCue::Jump),
+                _state: Jumping {},
+            }
+        }
+
+        pub fn knock_out(self) -> KnockOutEndState {
+            self.take_hit()
+        }
     }
 
     pub enum JumpingEndState {
@@ -272,14 +468,11 @@ mod red_hat_boy_states {
     }
 
     impl RedHatBoyState<Falling> {
-        pub fn frame_name(&self) -> &str {
-            FALLING_FRAME_NAME
-        }
-
         pub fn update(mut self) -> FallingEndState {
-            self.context = self.context.update(FALLING_FRAMES);
+            let (context, event) = self.context.animate();
+            self.context = context;
 
-            if self.context.frame >= FALLING_FRAMES {
+            if event == Some(AnimationEvent::Finished) {
                 FallingEndState::Complete(self.knock_out())
             } else {
                 FallingEndState::Falling(self)
@@ -299,15 +492,208 @@ mod red_hat_boy_states {
         Falling(RedHatBoyState<Falling>),
     }
 
-    impl RedHatBoyState<KnockedOut> {
-        pub fn frame_name(&self) -> &str {
-            FALLING_FRAME_NAME
+    pub enum KnockOutEndState {
+        Hurt(RedHatBoyState<Hurt>),
+        Falling(RedHatBoyState<Falling>),
+    }
+
+    impl RedHatBoyState<Hurt> {
+        pub fn update(mut self) -> HurtEndState {
+            let (context, event) = self.context.animate();
+            self.context = context;
+
+            if event == Some(AnimationEvent::Finished) {
+                HurtEndState::Complete(self.recover())
+            } else {
+                HurtEndState::Hurt(self)
+            }
+        }
+
+        pub fn recover(self) -> RedHatBoyState<Running> {
+            RedHatBoyState {
+                context: self.context.play(RUNNING_FRAME_NAME),
+                _state: Running {},
+            }
+        }
+
+        pub fn land_on(self, position: i16) -> RedHatBoyState<Hurt> {
+            RedHatBoyState {
+                context: self.context.set_on(position),
+                _state: Hurt {},
+            }
+        }
+
+        pub fn bounce(self) -> RedHatBoyState<Hurt> {
+            RedHatBoyState {
+                context: self.context.set_vertical_velocity(STOMP_BOUNCE),
+                _state: Hurt {},
+            }
+        }
+    }
+
+    pub enum HurtEndState {
+        Complete(RedHatBoyState<Running>),
+        Hurt(RedHatBoyState<Hurt>),
+    }
+}
+
+mod dog_states {
+    use crate::{engine::{self, Point, Sheet, Animation, Animations, Playback}, game::HEIGHT};
+    const DOG_HEIGHT: i16 = 56;
+    const FLOOR: i16 = HEIGHT - DOG_HEIGHT;
+    const STARTING_POINT: i16 = 160;
+    // Far enough ahead that the dog clears the boy while trailing him by the follow delay.
+    const FOLLOW_OFFSET: i16 = 180;
+
+    const WAITING_FRAME_NAME: &str = "Idle";
+    const FOLLOWING_FRAME_NAME: &str = "Run";
+    const JUMPING_FRAME_NAME: &str = "Jump";
+
+    const WAITING_FRAME_DURATION: u16 = 6;
+    const FOLLOWING_FRAME_DURATION: u16 = 3;
+    const JUMPING_FRAME_DURATION: u16 = 3;
+
+    #[derive(Clone)]
+    pub struct DogState<S> {
+        context: DogContext,
+        _state: S,
+    }
+
+    #[derive(Clone)]
+    pub struct DogContext {
+        pub animation: Animation,
+        animations: Animations,
+        pub position: Point,
+    }
+
+    #[derive(Copy, Clone)]
+    pub struct Waiting;
+
+    #[derive(Copy, Clone)]
+    pub struct Following;
+
+    #[derive(Copy, Clone)]
+    pub struct Jumping;
+
+    impl<S> DogState<S> {
+        pub fn context(&self) -> &DogContext {
+            &self.context
+        }
+
+        pub fn track(mut self, position: Point) -> Self {
+            self.context.position.x = position.x + FOLLOW_OFFSET;
+            self.context = self.context.set_on(position.y);
+            self
+        }
+
+        pub fn shift(mut self, x: i16) -> Self {
+            self.context.position.x += x;
+            self
+        }
+    }
+
+    pub fn dog_animations(sheet: &Sheet) -> Animations {
+        engine::animations(
+            sheet,
+            &[
+                (WAITING_FRAME_NAME, WAITING_FRAME_DURATION, Playback::PingPong),
+                (FOLLOWING_FRAME_NAME, FOLLOWING_FRAME_DURATION, Playback::Loop),
+                (JUMPING_FRAME_NAME, JUMPING_FRAME_DURATION, Playback::Once),
+            ],
+        )
+    }
+
+    impl DogContext {
+        pub fn update(mut self) -> Self {
+            self.animation.update();
+            self
+        }
+
+        fn play(mut self, name: &str) -> Self {
+            if let Some(animation) = self.animations.get(name) {
+                self.animation = animation.clone();
+            }
+            self
+        }
+
+        fn set_on(mut self, ground: i16) -> Self {
+            self.position.y = (ground - DOG_HEIGHT).min(FLOOR);
+            self
+        }
+    }
+
+    impl DogState<Waiting> {
+        pub fn new(animations: Animations) -> Self {
+            DogState {
+                context: DogContext {
+                    animation: animations[WAITING_FRAME_NAME].clone(),
+                    animations,
+                    position: Point { x: STARTING_POINT, y: FLOOR },
+                },
+                _state: Waiting {},
+            }
+        }
+
+        pub fn update(mut self) -> Self {
+            self.context = self.context.update();
+            self
+        }
+
+        pub fn follow(self) -> DogState<Following> {
+            DogState {
+                context: self.context.play(FOLLOWING_FRAME_NAME),
+                _state: Following {},
+            }
+        }
+    }
+
+    impl DogState<Following> {
+        pub fn update(mut self) -> Self {
+            self.context = self.context.update();
+            self
+        }
+
+        pub fn jump(self) -> DogState<Jumping> {
+            DogState {
+                context: self.context.play(JUMPING_FRAME_NAME),
+                _state: Jumping {},
+            }
+        }
+
+        pub fn wait(self) -> DogState<Waiting> {
+            DogState {
+                context: self.context.play(WAITING_FRAME_NAME),
+                _state: Waiting {},
+            }
+        }
+    }
+
+    impl DogState<Jumping> {
+        pub fn update(mut self) -> Self {
+            self.context = self.context.update();
+            self
+        }
+
+        pub fn land(self) -> DogState<Following> {
+            DogState {
+                context: self.context.play(FOLLOWING_FRAME_NAME),
+                _state: Following {},
+            }
+        }
+
+        pub fn wait(self) -> DogState<Waiting> {
+            DogState {
+                context: self.context.play(WAITING_FRAME_NAME),
+                _state: Waiting {},
+            }
         }
     }
-    
 }
 
+use crate::{engine::Point, sfx::SoundCue};
+
 pub use self::red_hat_boy_states::*;
+pub use self::dog_states::{DogState, DogContext, Waiting, Following, dog_animations};
 
 // pub use red_hat_boy_states::*;
 
@@ -319,14 +705,20 @@ pub enum RedHatBoyStateMachine {
     Jumping(RedHatBoyState<Jumping>),
     Falling(RedHatBoyState<Falling>),
     KnockedOut(RedHatBoyState<KnockedOut>),
+    Hurt(RedHatBoyState<Hurt>),
 }
 
 pub enum Event {
     Run,
     Slide,
     Jump,
+    AirJump,
     KnockOut,
     Land(i16),
+    PowerUp(PowerUp),
+    Collect,
+    Bounce,
+    Shift(i16),
     Update,
 }
 
@@ -336,43 +728,74 @@ impl RedHatBoyStateMachine {
             (RedHatBoyStateMachine::Idle(state), Event::Run) => state.run().into(),
             (RedHatBoyStateMachine::Running(state), Event::Slide) => state.slide().into(),
             (RedHatBoyStateMachine::Running(state), Event::Jump) => state.jump().into(),
+            (RedHatBoyStateMachine::Jumping(state), Event::AirJump)
+                if state.context().modifiers.can_air_jump() && state.context().velocity.y >= 0 =>
+            {
+                state.air_jump().into()
+            }
 
             (RedHatBoyStateMachine::Jumping(state), Event::Land(position)) => state.land_on(position).into(),
+            (RedHatBoyStateMachine::Running(state), Event::Bounce) => state.bounce().into(),
+            (RedHatBoyStateMachine::Sliding(state), Event::Bounce) => state.bounce().into(),
+            (RedHatBoyStateMachine::Jumping(state), Event::Bounce) => state.bounce().into(),
+            (RedHatBoyStateMachine::Hurt(state), Event::Bounce) => state.bounce().into(),
             (RedHatBoyStateMachine::Running(state), Event::Land(position)) => state.land_on(position).into(),
             (RedHatBoyStateMachine::Sliding(state), Event::Land(position)) => state.land_on(position).into(),
+            (RedHatBoyStateMachine::Hurt(state), Event::Land(position)) => state.land_on(position).into(),
             
+            (RedHatBoyStateMachine::Running(state), Event::KnockOut)
+                if state.context().modifiers.is_protected() => state.absorb_hit().into(),
+            (RedHatBoyStateMachine::Sliding(state), Event::KnockOut)
+                if state.context().modifiers.is_protected() => state.absorb_hit().into(),
+            (RedHatBoyStateMachine::Jumping(state), Event::KnockOut)
+                if state.context().modifiers.is_protected() => state.absorb_hit().into(),
             (RedHatBoyStateMachine::Running(state), Event::KnockOut) => state.knock_out().into(),
             (RedHatBoyStateMachine::Sliding(state), Event::KnockOut) => state.knock_out().into(),
             (RedHatBoyStateMachine::Jumping(state), Event::KnockOut) => state.knock_out().into(),
+
+            (RedHatBoyStateMachine::Idle(state), Event::PowerUp(power_up)) => state.power_up(power_up).into(),
+            (RedHatBoyStateMachine::Running(state), Event::PowerUp(power_up)) => state.power_up(power_up).into(),
+            (RedHatBoyStateMachine::Sliding(state), Event::PowerUp(power_up)) => state.power_up(power_up).into(),
+            (RedHatBoyStateMachine::Jumping(state), Event::PowerUp(power_up)) => state.power_up(power_up).into(),
+            (RedHatBoyStateMachine::Hurt(state), Event::PowerUp(power_up)) => state.power_up(power_up).into(),
+
+            (RedHatBoyStateMachine::Idle(state), Event::Collect) => state.collect().into(),
+            (RedHatBoyStateMachine::Running(state), Event::Collect) => state.collect().into(),
+            (RedHatBoyStateMachine::Sliding(state), Event::Collect) => state.collect().into(),
+            (RedHatBoyStateMachine::Jumping(state), Event::Collect) => state.collect().into(),
+            (RedHatBoyStateMachine::Hurt(state), Event::Collect) => state.collect().into(),
             
+            (RedHatBoyStateMachine::Idle(state), Event::Shift(x)) => state.shift(x).into(),
+            (RedHatBoyStateMachine::Running(state), Event::Shift(x)) => state.shift(x).into(),
+            (RedHatBoyStateMachine::Sliding(state), Event::Shift(x)) => state.shift(x).into(),
+            (RedHatBoyStateMachine::Jumping(state), Event::Shift(x)) => state.shift(x).into(),
+            (RedHatBoyStateMachine::Falling(state), Event::Shift(x)) => state.shift(x).into(),
+            (RedHatBoyStateMachine::KnockedOut(state), Event::Shift(x)) => state.shift(x).into(),
+            (RedHatBoyStateMachine::Hurt(state), Event::Shift(x)) => state.shift(x).into(),
+
             (RedHatBoyStateMachine::Idle(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Running(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Sliding(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Jumping(state), Event::Update) => state.update().into(),
             (RedHatBoyStateMachine::Falling(state), Event::Update) => state.update().into(),
+            (RedHatBoyStateMachine::Hurt(state), Event::Update) => state.update().into(),
             _ => self,
         }
     }
 
     pub fn frame_name(&self) -> &str {
-        match self {
-            RedHatBoyStateMachine::Idle(state) => state.frame_name(),
-            RedHatBoyStateMachine::Running(state) => state.frame_name(),
-            RedHatBoyStateMachine::Sliding(state) => state.frame_name(),
-            RedHatBoyStateMachine::Jumping(state) => state.frame_name(),
-            RedHatBoyStateMachine::Falling(state) => state.frame_name(),
-            RedHatBoyStateMachine::KnockedOut(state) => state.frame_name(),
-        }
+        self.context().animation.frame_name()
     }
 
     pub fn context(&self) -> &RedHatBoyContext {
         match self {
-            RedHatBoyStateMachine::Idle(state) => &state.context(),
-            RedHatBoyStateMachine::Running(state) => &state.context(),
-            RedHatBoyStateMachine::Sliding(state) => &state.context(),
-            RedHatBoyStateMachine::Jumping(state) => &state.context(),
-            RedHatBoyStateMachine::Falling(state) => &state.context(),
-            RedHatBoyStateMachine::KnockedOut(state) => &state.context(),
+            RedHatBoyStateMachine::Idle(state) => state.context(),
+            RedHatBoyStateMachine::Running(state) => state.context(),
+            RedHatBoyStateMachine::Sliding(state) => state.context(),
+            RedHatBoyStateMachine::Jumping(state) => state.context(),
+            RedHatBoyStateMachine::Falling(state) => state.context(),
+            RedHatBoyStateMachine::KnockedOut(state) => state.context(),
+            RedHatBoyStateMachine::Hurt(state) => state.context(),
         }
     }
 
@@ -380,9 +803,45 @@ impl RedHatBoyStateMachine {
         self.transition(Event::Update)
     }
 
+    pub fn take_sounds(&mut self) -> Vec<SoundCue> {
+        match self {
+            RedHatBoyStateMachine::Idle(state) => state.take_sounds(),
+            RedHatBoyStateMachine::Running(state) => state.take_sounds(),
+            RedHatBoyStateMachine::Sliding(state) => state.take_sounds(),
+            RedHatBoyStateMachine::Jumping(state) => state.take_sounds(),
+            RedHatBoyStateMachine::Falling(state) => state.take_sounds(),
+            RedHatBoyStateMachine::KnockedOut(state) => state.take_sounds(),
+            RedHatBoyStateMachine::Hurt(state) => state.take_sounds(),
+        }
+    }
+
     pub fn knocked_out(&self) -> bool {
         matches!(self, RedHatBoyStateMachine::KnockedOut(_))
     }
+
+    pub fn can_collect(&self) -> bool {
+        !matches!(
+            self,
+            RedHatBoyStateMachine::Falling(_) | RedHatBoyStateMachine::KnockedOut(_)
+        )
+    }
+
+    pub fn sliding(&self) -> bool {
+        matches!(self, RedHatBoyStateMachine::Sliding(_))
+    }
+
+    pub fn jumping(&self) -> bool {
+        matches!(self, RedHatBoyStateMachine::Jumping(_))
+    }
+
+    pub fn moving(&self) -> bool {
+        matches!(
+            self,
+            RedHatBoyStateMachine::Running(_)
+                | RedHatBoyStateMachine::Sliding(_)
+                | RedHatBoyStateMachine::Jumping(_)
+        )
+    }
 }
 
 impl From<RedHatBoyState<Idle>> for RedHatBoyStateMachine {
@@ -447,3 +906,315 @@ impl From<RedHatBoyState<KnockedOut>> for RedHatBoyStateMachine {
         RedHatBoyStateMachine::KnockedOut(state)
     }
 }
+
+impl From<RedHatBoyState<Hurt>> for RedHatBoyStateMachine {
+    fn from(state: RedHatBoyState<Hurt>) -> Self {
+        RedHatBoyStateMachine::Hurt(state)
+    }
+}
+
+impl From<HurtEndState> for RedHatBoyStateMachine {
+    fn from(end_state: HurtEndState) -> Self {
+        match end_state {
+            HurtEndState::Complete(running_state) => running_state.into(),
+            HurtEndState::Hurt(hurt_state) => hurt_state.into(),
+        }
+    }
+}
+
+impl From<KnockOutEndState> for RedHatBoyStateMachine {
+    fn from(end_state: KnockOutEndState) -> Self {
+        match end_state {
+            KnockOutEndState::Hurt(hurt_state) => hurt_state.into(),
+            KnockOutEndState::Falling(falling_state) => falling_state.into(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub enum DogStateMachine {
+    Waiting(DogState<Waiting>),
+    Following(DogState<Following>),
+    Jumping(DogState<dog_states::Jumping>),
+}
+
+pub enum DogEvent {
+    Follow,
+    Jump,
+    Land,
+    Wait,
+    Track(Point),
+    Shift(i16),
+    Update,
+}
+
+impl DogStateMachine {
+    pub fn transition(self, event: DogEvent) -> Self {
+        match (self.clone(), event) {
+            (DogStateMachine::Waiting(state), DogEvent::Follow) => state.follow().into(),
+            (DogStateMachine::Following(state), DogEvent::Jump) => state.jump().into(),
+            (DogStateMachine::Jumping(state), DogEvent::Land) => state.land().into(),
+
+            (DogStateMachine::Following(state), DogEvent::Wait) => state.wait().into(),
+            (DogStateMachine::Jumping(state), DogEvent::Wait) => state.wait().into(),
+
+            (DogStateMachine::Waiting(state), DogEvent::Track(position)) => state.track(position).into(),
+            (DogStateMachine::Following(state), DogEvent::Track(position)) => state.track(position).into(),
+            (DogStateMachine::Jumping(state), DogEvent::Track(position)) => state.track(position).into(),
+
+            (DogStateMachine::Waiting(state), DogEvent::Shift(x)) => state.shift(x).into(),
+            (DogStateMachine::Following(state), DogEvent::Shift(x)) => state.shift(x).into(),
+            (DogStateMachine::Jumping(state), DogEvent::Shift(x)) => state.shift(x).into(),
+
+            (DogStateMachine::Waiting(state), DogEvent::Update) => state.update().into(),
+            (DogStateMachine::Following(state), DogEvent::Update) => state.update().into(),
+            (DogStateMachine::Jumping(state), DogEvent::Update) => state.update().into(),
+            _ => self,
+        }
+    }
+
+    pub fn frame_name(&self) -> &str {
+        self.context().animation.frame_name()
+    }
+
+    pub fn context(&self) -> &DogContext {
+        match self {
+            DogStateMachine::Waiting(state) => state.context(),
+            DogStateMachine::Following(state) => state.context(),
+            DogStateMachine::Jumping(state) => state.context(),
+        }
+    }
+
+    pub fn update(self) -> Self {
+        self.transition(DogEvent::Update)
+    }
+}
+
+impl From<DogState<Waiting>> for DogStateMachine {
+    fn from(state: DogState<Waiting>) -> Self {
+        DogStateMachine::Waiting(state)
+    }
+}
+
+impl From<DogState<Following>> for DogStateMachine {
+    fn from(state: DogState<Following>) -> Self {
+        DogStateMachine::Following(state)
+    }
+}
+
+impl From<DogState<dog_states::Jumping>> for DogStateMachine {
+    fn from(state: DogState<dog_states::Jumping>) -> Self {
+        DogStateMachine::Jumping(state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        assets::Assets,
+        engine::{Cell, Sheet, SheetRect, Sound},
+        sfx::{RecordingAudio, SoundEvent, SoundEvents},
+    };
+
+    fn sheet() -> Sheet {
+        let cell = Cell {
+            frame: SheetRect { x: 0, y: 0, w: 1, h: 1 },
+            rotated: false,
+            trimmed: false,
+            sprite_source_size: SheetRect { x: 0, y: 0, w: 1, h: 1 },
+            source_size: None,
+            pivot: None,
+        };
+
+        Sheet {
+            frames: ["Idle", "Run", "Slide", "Jump", "Dead", "Hurt"]
+                .iter()
+                .map(|name| (format!("{} (1).png", name), cell.clone()))
+                .collect(),
+            meta: None,
+        }
+    }
+
+    #[test]
+    fn jumping_queues_the_jump_sound_once() {
+        let mut boy = RedHatBoyStateMachine::Idle(RedHatBoyState::new(
+            red_hat_boy_animations(&sheet()),
+        ))
+        .transition(Event::Run)
+        .transition(Event::Jump);
+
+        assert!(boy.jumping());
+        assert_eq!(boy.take_sounds(), vec![SoundCue::Jump]);
+        assert!(boy.take_sounds().is_empty());
+    }
+
+    #[test]
+    fn holding_space_plays_the_jump_sound_exactly_once() {
+        let mut assets = Assets::new();
+        let jump = assets.register::<Sound>("jump.mp3");
+        let land = assets.register::<Sound>("land");
+        let mut events = SoundEvents::default()
+            .with(SoundCue::Jump, SoundEvent::new(vec![jump]))
+            .with(SoundCue::Land, SoundEvent::new(vec![land]));
+        let mut audio = RecordingAudio::default();
+        let mut boy = RedHatBoyStateMachine::Idle(RedHatBoyState::new(
+            red_hat_boy_animations(&sheet()),
+        ))
+        .transition(Event::Run);
+
+        for tick in 0..20 {
+            let space_pressed = (3..9).contains(&tick);
+            if space_pressed {
+                boy = boy.transition(Event::Jump);
+            }
+            boy = boy.update();
+
+            events.update();
+            for cue in boy.take_sounds() {
+                events.play(cue, None, &mut audio).unwrap();
+            }
+            audio.tick();
+        }
+
+        assert_eq!(audio.count(jump), 1);
+        assert_eq!(audio.played()[0].tick, 3);
+        assert_eq!(audio.played()[0].position, None);
+    }
+
+    fn running_boy() -> RedHatBoyStateMachine {
+        RedHatBoyStateMachine::Idle(RedHatBoyState::new(red_hat_boy_animations(&sheet())))
+            .transition(Event::Run)
+    }
+
+    #[test]
+    fn power_ups_run_out_and_a_shield_absorbs_one_hit() {
+        let mut boy = running_boy()
+            .transition(Event::PowerUp(PowerUp::Shield))
+            .transition(Event::PowerUp(PowerUp::Magnet));
+        let duration = boy.context().modifiers.remaining(PowerUp::Magnet);
+        assert!(duration > 0);
+
+        boy = boy.update();
+        assert_eq!(boy.context().modifiers.remaining(PowerUp::Magnet), duration - 1);
+
+        boy = boy.transition(Event::KnockOut);
+        assert!(!boy.context().modifiers.is_active(PowerUp::Shield));
+        assert!(boy.context().modifiers.is_protected());
+        assert!(boy.moving());
+
+        for _ in 1..duration {
+            boy = boy.update();
+        }
+        assert!(!boy.context().modifiers.is_active(PowerUp::Magnet));
+        assert!(!boy.context().modifiers.is_protected());
+    }
+
+    #[test]
+    fn the_air_jump_is_spent_after_the_apex_and_reset_on_landing() {
+        let mut boy = running_boy()
+            .transition(Event::PowerUp(PowerUp::DoubleJump))
+            .transition(Event::Jump);
+        boy.take_sounds();
+
+        boy = boy.transition(Event::AirJump);
+        assert!(boy.take_sounds().is_empty(), "no air jump while rising");
+
+        while boy.context().velocity.y < 0 {
+            boy = boy.update();
+        }
+        boy.take_sounds();
+        boy = boy.transition(Event::AirJump);
+        assert!(boy.context().velocity.y < 0);
+        assert_eq!(boy.take_sounds(), vec![SoundCue::Jump]);
+        assert!(!boy.context().modifiers.can_air_jump());
+
+        while boy.context().velocity.y < 0 {
+            boy = boy.update();
+        }
+        boy = boy.transition(Event::AirJump);
+        assert!(boy.context().velocity.y >= 0, "only one air jump per jump");
+
+        boy = boy.transition(Event::Land(500));
+        assert!(boy.context().modifiers.can_air_jump());
+    }
+
+    #[test]
+    fn pickups_count_while_hurt_but_not_once_falling() {
+        let boy = running_boy().transition(Event::KnockOut);
+        assert!(boy.can_collect());
+        assert_eq!(boy.transition(Event::Collect).context().collected, 1);
+
+        let mut boy = running_boy();
+        while boy.can_collect() {
+            boy = boy.transition(Event::KnockOut);
+            while boy.can_collect() && boy.context().modifiers.is_protected() {
+                boy = boy.update();
+            }
+        }
+        assert_eq!(boy.context().lives, 0);
+    }
+
+    #[test]
+    fn a_hit_costs_a_life_and_hurt_recovers_to_running() {
+        let mut boy = running_boy();
+        let lives = boy.context().lives;
+
+        boy = boy.transition(Event::KnockOut);
+        assert!(matches!(boy, RedHatBoyStateMachine::Hurt(_)));
+        assert_eq!(boy.context().lives, lives - 1);
+        assert!(boy.context().modifiers.is_protected());
+
+        boy = boy.transition(Event::KnockOut);
+        assert_eq!(boy.context().lives, lives - 1, "no further damage while hurt");
+
+        for _ in 0..10 {
+            boy = boy.update();
+        }
+        assert!(matches!(boy, RedHatBoyStateMachine::Running(_)));
+        assert!(boy.context().modifiers.is_protected());
+    }
+
+    #[test]
+    fn finished_animations_end_slides_falls_and_hurts() {
+        // Every animation in the test sheet is a single frame shown for three ticks.
+        const FRAME_DURATION: u16 = 3;
+        let mut boy = running_boy().transition(Event::Slide);
+        for _ in 1..FRAME_DURATION {
+            boy = boy.update();
+        }
+        assert!(boy.sliding());
+        boy = boy.update();
+        assert!(matches!(boy, RedHatBoyStateMachine::Running(_)));
+
+        boy = boy.transition(Event::KnockOut);
+        for _ in 1..FRAME_DURATION {
+            boy = boy.update();
+        }
+        assert!(matches!(boy, RedHatBoyStateMachine::Hurt(_)));
+        boy = boy.update();
+        assert!(matches!(boy, RedHatBoyStateMachine::Running(_)));
+
+        while boy.context().lives > 1 {
+            boy = boy.transition(Event::KnockOut);
+            while boy.context().modifiers.is_protected() {
+                boy = boy.update();
+            }
+        }
+        boy = boy.transition(Event::KnockOut);
+        for _ in 1..FRAME_DURATION {
+            boy = boy.update();
+        }
+        assert!(!boy.knocked_out());
+        boy = boy.update();
+        assert!(boy.knocked_out());
+    }
+
+    #[test]
+    fn stomping_after_walking_off_a_ledge_bounces_the_boy() {
+        let boy = running_boy().update().transition(Event::Bounce);
+
+        assert!(boy.jumping());
+        assert!(boy.context().velocity.y < 0);
+    }
+}
@@ -0,0 +1,305 @@
+use std::f32::consts::PI;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Easing {
+    Linear,
+    QuadIn,
+    QuadOut,
+    QuadInOut,
+    CubicIn,
+    CubicOut,
+    CubicInOut,
+    ElasticOut,
+    BounceOut,
+    BackIn,
+    BackOut,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TweenEvent {
+    Completed,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        const BACK: f32 = 1.70158;
+
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+            Easing::QuadInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+                }
+            }
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+            Easing::CubicInOut => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+                }
+            }
+            Easing::ElasticOut => {
+                if t == 0.0 || t == 1.0 {
+                    t
+                } else {
+                    2.0_f32.powf(-10.0 * t) * ((t * 10.0 - 0.75) * (2.0 * PI / 3.0)).sin() + 1.0
+                }
+            }
+            Easing::BounceOut => bounce_out(t),
+            Easing::BackIn => (BACK + 1.0) * t * t * t - BACK * t * t,
+            Easing::BackOut => {
+                1.0 + (BACK + 1.0) * (t - 1.0).powi(3) + BACK * (t - 1.0).powi(2)
+            }
+        }
+    }
+}
+
+fn bounce_out(t: f32) -> f32 {
+    const N: f32 = 7.5625;
+    const D: f32 = 2.75;
+
+    if t < 1.0 / D {
+        N * t * t
+    } else if t < 2.0 / D {
+        let t = t - 1.5 / D;
+        N * t * t + 0.75
+    } else if t < 2.5 / D {
+        let t = t - 2.25 / D;
+        N * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D;
+        N * t * t + 0.984375
+    }
+}
+
+struct Step {
+    from: f32,
+    to: f32,
+    duration: u16,
+    easing: Easing,
+}
+
+pub struct Tween {
+    steps: Vec<Step>,
+    delay: u16,
+    tick: u32,
+    yoyo: bool,
+    repeat: bool,
+}
+
+impl Tween {
+    pub fn new(from: f32, to: f32, duration: u16, easing: Easing) -> Self {
+        Tween {
+            steps: vec![Step {
+                from,
+                to,
+                duration: duration.max(1),
+                easing,
+            }],
+            delay: 0,
+            tick: 0,
+            yoyo: false,
+            repeat: false,
+        }
+    }
+
+    pub fn then(mut self, to: f32, duration: u16, easing: Easing) -> Self {
+        let from = self.steps.last().map_or(to, |step| step.to);
+        self.steps.push(Step {
+            from,
+            to,
+            duration: duration.max(1),
+            easing,
+        });
+        self
+    }
+
+    pub fn delay(mut self, frames: u16) -> Self {
+        self.delay = frames;
+        self
+    }
+
+    pub fn yoyo(mut self) -> Self {
+        self.yoyo = true;
+        self
+    }
+
+    pub fn repeat(mut self) -> Self {
+        self.repeat = true;
+        self
+    }
+
+    pub fn update(&mut self) -> Option<TweenEvent> {
+        if self.is_complete() {
+            return None;
+        }
+
+        self.tick += 1;
+        if self.is_complete() {
+            Some(TweenEvent::Completed)
+        } else {
+            None
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.repeat && self.tick >= self.delay as u32 + self.cycle()
+    }
+
+    pub fn value(&self) -> f32 {
+        let elapsed = self.tick.saturating_sub(self.delay as u32);
+        let total = self.total();
+        let position = if self.repeat {
+            elapsed % self.cycle()
+        } else {
+            elapsed.min(self.cycle())
+        };
+        let position = if position > total {
+            2 * total - position
+        } else {
+            position
+        };
+
+        let mut start = 0;
+        for step in &self.steps {
+            let end = start + step.duration as u32;
+            if position <= end {
+                let progress = (position - start) as f32 / step.duration as f32;
+                return step.from + (step.to - step.from) * step.easing.apply(progress);
+            }
+            start = end;
+        }
+        self.steps.last().map_or(0.0, |step| step.to)
+    }
+
+    fn total(&self) -> u32 {
+        self.steps.iter().map(|step| step.duration as u32).sum()
+    }
+
+    fn cycle(&self) -> u32 {
+        if self.yoyo {
+            self.total() * 2
+        } else {
+            self.total()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn advance(tween: &mut Tween, frames: u16) {
+        (0..frames).for_each(|_| {
+            tween.update();
+        });
+    }
+
+    #[test]
+    fn easings_start_at_zero_and_end_at_one() {
+        [
+            Easing::Linear,
+            Easing::QuadIn,
+            Easing::QuadOut,
+            Easing::QuadInOut,
+            Easing::CubicIn,
+            Easing::CubicOut,
+            Easing::CubicInOut,
+            Easing::ElasticOut,
+            Easing::BounceOut,
+            Easing::BackIn,
+            Easing::BackOut,
+        ]
+        .iter()
+        .for_each(|easing| {
+            assert!(easing.apply(0.0).abs() < 0.001, "{:?}", easing);
+            assert!((easing.apply(1.0) - 1.0).abs() < 0.001, "{:?}", easing);
+        });
+    }
+
+    #[test]
+    fn sequence_with_delay_completes_after_its_last_step() {
+        let mut tween = Tween::new(0.0, 10.0, 10, Easing::QuadIn)
+            .then(20.0, 10, Easing::QuadOut)
+            .delay(5);
+
+        advance(&mut tween, 5);
+        assert_eq!(tween.value(), 0.0);
+
+        advance(&mut tween, 5);
+        assert_eq!(tween.value(), 2.5);
+
+        advance(&mut tween, 5);
+        assert_eq!(tween.value(), 10.0);
+
+        let events: Vec<Option<TweenEvent>> = (0..20).map(|_| tween.update()).collect();
+        assert_eq!(events[9], Some(TweenEvent::Completed));
+        assert_eq!(events.iter().flatten().count(), 1);
+        assert_eq!(tween.value(), 20.0);
+        assert!(tween.is_complete());
+    }
+
+    #[test]
+    fn easings_have_their_characteristic_shapes() {
+        assert_eq!(Easing::Linear.apply(0.25), 0.25);
+        assert!(Easing::CubicIn.apply(0.5) < Easing::QuadIn.apply(0.5));
+        assert!((Easing::CubicInOut.apply(0.5) - 0.5).abs() < 0.001);
+        assert!(Easing::BackIn.apply(0.2) < 0.0, "back in pulls back first");
+        assert!(Easing::BackOut.apply(0.8) > 1.0, "back out overshoots");
+        assert!(Easing::ElasticOut.apply(0.2) > 1.0, "elastic out overshoots");
+        assert!(
+            Easing::BounceOut.apply(0.4) > Easing::BounceOut.apply(0.5),
+            "bounce out dips after the first landing"
+        );
+    }
+
+    #[test]
+    fn yoyo_tweens_play_back_in_reverse_then_complete() {
+        let mut tween = Tween::new(0.0, 10.0, 10, Easing::Linear).yoyo();
+
+        advance(&mut tween, 5);
+        assert_eq!(tween.value(), 5.0);
+
+        advance(&mut tween, 5);
+        assert_eq!(tween.value(), 10.0);
+
+        advance(&mut tween, 3);
+        assert_eq!(tween.value(), 7.0);
+        assert!(!tween.is_complete());
+
+        let events: Vec<Option<TweenEvent>> = (0..7).map(|_| tween.update()).collect();
+        assert_eq!(events[6], Some(TweenEvent::Completed));
+        assert_eq!(tween.value(), 0.0);
+    }
+
+    #[test]
+    fn repeating_tweens_wrap_around() {
+        let mut tween = Tween::new(0.0, 10.0, 10, Easing::QuadIn)
+            .then(0.0, 10, Easing::QuadOut)
+            .repeat();
+
+        let events: Vec<Option<TweenEvent>> = (0..25).map(|_| tween.update()).collect();
+        assert_eq!(tween.value(), 2.5);
+        assert!(!tween.is_complete());
+        assert!(events.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn repeating_yoyo_tweens_ping_pong() {
+        let mut tween = Tween::new(0.0, 10.0, 10, Easing::Linear).yoyo().repeat();
+
+        advance(&mut tween, 15);
+        assert_eq!(tween.value(), 5.0);
+
+        advance(&mut tween, 10);
+        assert_eq!(tween.value(), 5.0);
+        assert!(!tween.is_complete());
+    }
+}
@@ -0,0 +1,281 @@
+use anyhow::{anyhow, Result};
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Element, HtmlElement, KeyboardEvent, NodeList};
+
+use crate::browser;
+
+pub struct Button<E> {
+    label: String,
+    event: E,
+}
+
+pub struct Label {
+    text: String,
+}
+
+pub struct Panel<E> {
+    class: Option<&'static str>,
+    children: Vec<Widget<E>>,
+}
+
+pub struct List<E> {
+    items: Vec<Widget<E>>,
+}
+
+pub enum Widget<E> {
+    Button(Button<E>),
+    Label(Label),
+    Panel(Panel<E>),
+    List(List<E>),
+}
+
+pub struct Screen<E> {
+    root: HtmlElement,
+    events: UnboundedReceiver<E>,
+    _on_key_down: Closure<dyn FnMut(KeyboardEvent)>,
+    _on_clicks: Vec<Closure<dyn FnMut()>>,
+}
+
+impl<E> Button<E> {
+    pub fn new(label: &str, event: E) -> Self {
+        Button {
+            label: label.to_string(),
+            event,
+        }
+    }
+}
+
+impl Label {
+    pub fn new(text: &str) -> Self {
+        Label {
+            text: text.to_string(),
+        }
+    }
+}
+
+impl<E> Panel<E> {
+    pub fn new() -> Self {
+        Panel {
+            class: None,
+            children: vec![],
+        }
+    }
+
+    pub fn with_class(mut self, class: &'static str) -> Self {
+        self.class = Some(class);
+        self
+    }
+
+    pub fn child(mut self, child: impl Into<Widget<E>>) -> Self {
+        self.children.push(child.into());
+        self
+    }
+}
+
+impl<E> List<E> {
+    pub fn new() -> Self {
+        List { items: vec![] }
+    }
+
+    pub fn item(mut self, item: impl Into<Widget<E>>) -> Self {
+        self.items.push(item.into());
+        self
+    }
+}
+
+impl<E> Default for Panel<E> {
+    fn default() -> Self {
+        Panel::new()
+    }
+}
+
+impl<E> Default for List<E> {
+    fn default() -> Self {
+        List::new()
+    }
+}
+
+impl<E> From<Button<E>> for Widget<E> {
+    fn from(button: Button<E>) -> Self {
+        Widget::Button(button)
+    }
+}
+
+impl<E> From<Label> for Widget<E> {
+    fn from(label: Label) -> Self {
+        Widget::Label(label)
+    }
+}
+
+impl<E> From<Panel<E>> for Widget<E> {
+    fn from(panel: Panel<E>) -> Self {
+        Widget::Panel(panel)
+    }
+}
+
+impl<E> From<List<E>> for Widget<E> {
+    fn from(list: List<E>) -> Self {
+        Widget::List(list)
+    }
+}
+
+impl<E> Screen<E> {
+    pub fn try_next_event(&mut self) -> Option<E> {
+        self.events.try_next().ok().flatten()
+    }
+
+    pub fn focused(&self) -> usize {
+        focused_button(&self.root).unwrap_or(0)
+    }
+
+    pub fn close(self) -> Result<()> {
+        self.root.remove();
+        browser::canvas()?
+            .focus()
+            .map_err(|err| anyhow!("Could not set focus to canvas! {:#?}", err))
+    }
+}
+
+pub fn show<E: Clone + 'static>(widget: impl Into<Widget<E>>) -> Result<Screen<E>> {
+    show_focused(widget, 0)
+}
+
+pub fn show_focused<E: Clone + 'static>(
+    widget: impl Into<Widget<E>>,
+    focus: usize,
+) -> Result<Screen<E>> {
+    let (sender, events) = unbounded();
+    let mut on_clicks = vec![];
+    let root = build(widget.into(), &sender, &mut on_clicks)?
+        .dyn_into::<HtmlElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlElement", element))?;
+
+    let navigation_root = root.clone();
+    let on_key_down = browser::closure_wrap(Box::new(move |event: KeyboardEvent| {
+        let step = match event.code().as_str() {
+            "ArrowDown" => 1,
+            "ArrowUp" => -1,
+            _ => return,
+        };
+        event.prevent_default();
+        if let Err(err) = move_focus(&navigation_root, step) {
+            error!("Could not move focus {:#?}", err);
+        }
+    }) as Box<dyn FnMut(KeyboardEvent)>);
+    root.set_onkeydown(Some(on_key_down.as_ref().unchecked_ref()));
+
+    browser::ui()?
+        .append_child(&root)
+        .map_err(|err| anyhow!("Could not show ui {:#?}", err))?;
+    focus_button(&root, focus)?;
+
+    Ok(Screen {
+        root,
+        events,
+        _on_key_down: on_key_down,
+        _on_clicks: on_clicks,
+    })
+}
+
+fn build<E: Clone + 'static>(
+    widget: Widget<E>,
+    sender: &UnboundedSender<E>,
+    on_clicks: &mut Vec<Closure<dyn FnMut()>>,
+) -> Result<Element> {
+    match widget {
+        Widget::Button(button) => {
+            let element = browser::create_element("button")?
+                .dyn_into::<HtmlElement>()
+                .map_err(|element| anyhow!("Error converting {:#?} to HtmlElement", element))?;
+            element.set_text_content(Some(&button.label));
+
+            let sender = sender.clone();
+            let on_click = browser::closure_wrap(Box::new(move || {
+                if let Err(err) = sender.unbounded_send(button.event.clone()) {
+                    error!("Could not send button event {:#?}", err);
+                }
+            }) as Box<dyn FnMut()>);
+            element.set_onclick(Some(on_click.as_ref().unchecked_ref()));
+            on_clicks.push(on_click);
+
+            Ok(element.into())
+        }
+        Widget::Label(label) => {
+            let element = browser::create_element("p")?;
+            element.set_text_content(Some(&label.text));
+            Ok(element)
+        }
+        Widget::Panel(panel) => {
+            let element = browser::create_element("div")?;
+            let class = match panel.class {
+                Some(class) => format!("panel {}", class),
+                None => "panel".to_string(),
+            };
+            element.set_class_name(&class);
+            for child in panel.children {
+                append(&element, build(child, sender, on_clicks)?)?;
+            }
+            Ok(element)
+        }
+        Widget::List(list) => {
+            let element = browser::create_element("ul")?;
+            element.set_class_name("list");
+            for item in list.items {
+                let entry = browser::create_element("li")?;
+                append(&entry, build(item, sender, on_clicks)?)?;
+                append(&element, entry)?;
+            }
+            Ok(element)
+        }
+    }
+}
+
+fn append(parent: &Element, child: Element) -> Result<()> {
+    parent
+        .append_child(&child)
+        .map(|_| ())
+        .map_err(|err| anyhow!("Could not append ui element {:#?}", err))
+}
+
+fn buttons(root: &HtmlElement) -> Result<NodeList> {
+    root.query_selector_all("button")
+        .map_err(|err| anyhow!("Could not find buttons {:#?}", err))
+}
+
+fn focused_button(root: &HtmlElement) -> Option<usize> {
+    let buttons = buttons(root).ok()?;
+    let active = browser::document().ok()?.active_element()?;
+    (0..buttons.length()).position(|index| {
+        matches!(buttons.item(index), Some(button) if button.is_same_node(Some(active.as_ref())))
+    })
+}
+
+fn focus_button(root: &HtmlElement, index: usize) -> Result<()> {
+    let buttons = buttons(root)?;
+    if buttons.length() == 0 {
+        return Ok(());
+    }
+
+    let index = index.min(buttons.length() as usize - 1);
+    buttons
+        .item(index as u32)
+        .ok_or_else(|| anyhow!("Button {} not found", index))?
+        .dyn_into::<HtmlElement>()
+        .map_err(|element| anyhow!("Error converting {:#?} to HtmlElement", element))?
+        .focus()
+        .map_err(|err| anyhow!("Could not focus button {:#?}", err))
+}
+
+fn move_focus(root: &HtmlElement, step: i32) -> Result<()> {
+    let count = buttons(root)?.length() as i32;
+    if count == 0 {
+        return Ok(());
+    }
+
+    let next = match focused_button(root) {
+        Some(index) => (index as i32 + step).rem_euclid(count),
+        None => 0,
+    };
+    focus_button(root, next as usize)
+}
@@ -1,6 +1,3 @@
-use futures::prelude::*;
-use wasm_bindgen::JsValue;
-use wasm_bindgen_futures::JsFuture;
 use wasm_bindgen_test::{wasm_bindgen_test_configure, wasm_bindgen_test};
 
 wasm_bindgen_test_configure!(run_in_browser);
@@ -8,6 +5,7 @@ wasm_bindgen_test_configure!(run_in_browser);
 
 // This runs a unit test in native Rust, so it can only use Rust APIs.
 #[test]
+#[allow(clippy::eq_op)]
 fn rust_test() {
     assert_eq!(1, 1);
 }
@@ -15,6 +13,7 @@ fn rust_test() {
 
 // This runs a unit test in the browser, so it can use browser APIs.
 #[wasm_bindgen_test]
+#[allow(clippy::eq_op)]
 fn web_test() {
     assert_eq!(1, 1);
 }